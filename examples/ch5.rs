@@ -1,7 +1,7 @@
 use rtw::*;
 
 fn color(ray: &Ray, hitable: &HitableList) -> Color {
-    if let Some(rec) = hitable.hit(&ray, 0.0, std::f32::MAX) {
+    if let Some(rec) = hitable.hit(ray, 0.0, f32::MAX) {
         0.5 * (rec.normal + Vec3::unit())
     } else {
         let unit_dir = ray.direction.normalize();
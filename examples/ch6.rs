@@ -1,9 +1,8 @@
 use rtw::*;
-use rand::prelude::*;
 use rayon::prelude::*;
 
 fn color(ray: &Ray, hitable: &HitableList) -> Color {
-    if let Some(rec) = hitable.hit(&ray, 0.0, std::f32::MAX) {
+    if let Some(rec) = hitable.hit(ray, 0.0, f32::MAX) {
         0.5 * (rec.normal + Vec3::unit())
     } else {
         let unit_dir = ray.direction.normalize();
@@ -39,10 +38,11 @@ fn main() {
         let v = (ny - y - 1) as f32;
 
         let c = sample_range.par_iter()
-            .map(|_| {
-                let u = (u + thread_rng().gen::<f32>()) / nx as f32;
-                let v = (v + thread_rng().gen::<f32>()) / ny as f32;
-                let ray = cam.get_ray(u, v);
+            .map(|&s| {
+                let mut sampler = Sampler::new(0, u64::from(y * nx + x), s);
+                let u = (u + sampler.next_f32()) / nx as f32;
+                let v = (v + sampler.next_f32()) / ny as f32;
+                let ray = cam.get_ray(u, v, &mut sampler);
 
                 color(&ray, &list)
             })
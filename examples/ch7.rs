@@ -1,11 +1,11 @@
 use rtw::*;
-use rand::prelude::*;
+use rtw::scene::Scene;
 use rayon::prelude::*;
 
-fn color(ray: &Ray, hitable: &HitableList) -> Color {
-    if let Some(rec) = hitable.hit(&ray, 0.001, std::f32::MAX) {
-        let target = rec.point + rec.normal + random_in_unit_sphere();
-        0.5 * color(&Ray::new(rec.point, target - rec.point), hitable)
+fn color(ray: &Ray, hitable: &HitableList, sampler: &mut Sampler) -> Color {
+    if let Some(rec) = hitable.hit(ray, 0.001, f32::MAX) {
+        let target = rec.point + rec.normal + random_in_unit_sphere(sampler);
+        0.5 * color(&Ray::new(rec.point, target - rec.point), hitable, sampler)
     } else {
         let unit_dir = ray.direction.normalize();
         let t = 0.5 * (unit_dir.y + 1.0);
@@ -21,17 +21,9 @@ fn main() {
 
     let mut imgbuf = image::ImageBuffer::new(nx, ny);
 
-    let mut list = HitableList::default();
-    list.push(Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5,
-                Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
-    list.push(Sphere::new(Vec3::new(0.0, -100.5, -1.0), 100.0,
-                Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
-
-    let cam = Camera::new(
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 0.0, -1.0),
-        Vec3::new(0.0, 1.0, 0.0),
-        90.0, 2.0, 0.0, 1.0);
+    // the scene materials are ignored, every surface is the diffuse of this chapter
+    let Scene { world: list, camera, .. } = builtin::load("ch7", 0).unwrap();
+    let cam = camera.build(nx as f32 / ny as f32);
 
     let sample_range = (0..ns).collect::<Vec<_>>();
 
@@ -40,12 +32,13 @@ fn main() {
         let v = (ny - y - 1) as f32;
 
         let c = sample_range.par_iter()
-            .map(|_| {
-                let u = (u + thread_rng().gen::<f32>()) / nx as f32;
-                let v = (v + thread_rng().gen::<f32>()) / ny as f32;
-                let ray = cam.get_ray(u, v);
+            .map(|&s| {
+                let mut sampler = Sampler::new(0, u64::from(y * nx + x), s);
+                let u = (u + sampler.next_f32()) / nx as f32;
+                let v = (v + sampler.next_f32()) / ny as f32;
+                let ray = cam.get_ray(u, v, &mut sampler);
 
-                color(&ray, &list)
+                color(&ray, &list, &mut sampler)
             })
             .sum::<Color>() / ns as f32;
 
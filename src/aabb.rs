@@ -0,0 +1,93 @@
+use crate::Ray;
+use crate::Vec3;
+
+/// axis-aligned bounding box
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// construct new bounding box from `min` and `max` corner
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// smallest box containing both `a` and `b`
+    pub fn surrounding(a: &Aabb, b: &Aabb) -> Self {
+        Aabb {
+            min: Vec3::new(a.min.x.min(b.min.x), a.min.y.min(b.min.y), a.min.z.min(b.min.z)),
+            max: Vec3::new(a.max.x.max(b.max.x), a.max.y.max(b.max.y), a.max.z.max(b.max.z)),
+        }
+    }
+
+    /// center of box
+    pub fn centroid(&self) -> Vec3 {
+        0.5 * (self.min + self.max)
+    }
+
+    /// surface area of box
+    pub fn area(&self) -> f32 {
+        let d = self.max - self.min;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// index of the longest axis
+    pub fn longest_axis(&self) -> usize {
+        let d = self.max - self.min;
+        match (d.x > d.y, d.x > d.z, d.y > d.z) {
+            (true, true, _) => 0,
+            (false, _, true) => 1,
+            _ => 2,
+        }
+    }
+
+    /// test ray box intersection constrained by `t_min` and `t_max` (slab method)
+    pub fn hit(&self, ray: &Ray, mut t_min: f32, mut t_max: f32) -> bool {
+        for a in 0..3 {
+            let inv_d = ray.direction[a].recip();
+            let t0 = (self.min[a] - ray.origin[a]) * inv_d;
+            let t1 = (self.max[a] - ray.origin[a]) * inv_d;
+            let (t0, t1) = match inv_d < 0.0 {
+                true => (t1, t0),
+                false => (t0, t1),
+            };
+
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max <= t_min {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hit() {
+        let b = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+
+        let r = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(b.hit(&r, 0.0, f32::MAX));
+        assert!(!b.hit(&r, 0.0, 3.0));
+
+        let r = Ray::new(Vec3::new(2.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(!b.hit(&r, 0.0, f32::MAX));
+    }
+
+    #[test]
+    fn test_surrounding() {
+        let a = Aabb::new(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 1.0));
+        let b = Aabb::new(Vec3::new(0.0, -2.0, 0.0), Vec3::new(3.0, 0.0, 1.0));
+        let s = Aabb::surrounding(&a, &b);
+        assert_eq!(s.min, Vec3::new(-1.0, -2.0, 0.0));
+        assert_eq!(s.max, Vec3::new(3.0, 1.0, 1.0));
+        assert_eq!(s.longest_axis(), 0);
+    }
+}
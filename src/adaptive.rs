@@ -0,0 +1,190 @@
+use rayon::prelude::*;
+
+use crate::{luminance, Camera, Color, FrameBuffer, Hitable, Renderer, Vec3};
+
+/// adaptive sampling settings
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdaptiveSettings {
+    /// samples every pixel gets before its error is estimated
+    pub min_samples: u32,
+    /// upper limit of samples of a single pixel
+    pub max_samples: u32,
+    /// samples added to each unconverged pixel per round
+    pub batch: u32,
+    /// pixel is converged once the relative standard error of its luminance falls below this
+    pub threshold: f32,
+}
+
+impl AdaptiveSettings {
+    /// construct settings with error `threshold` for renders of `renderer`
+    ///
+    /// pixels start with a quarter of `renderer.samples` and may take up to 8 times as many
+    pub fn new(renderer: &Renderer, threshold: f32) -> Self {
+        AdaptiveSettings {
+            min_samples: (renderer.samples / 4).max(4),
+            max_samples: renderer.samples.max(1) * 8,
+            batch: (renderer.samples / 8).max(2),
+            threshold,
+        }
+    }
+}
+
+/// running per-pixel statistics
+#[derive(Copy, Clone)]
+struct PixelStats {
+    samples: u32,
+    sum: Color,
+    mean: f32,
+    m2: f32,
+}
+
+impl PixelStats {
+    fn add(&mut self, c: Color) {
+        // welford's online variance of luminance
+        let l = luminance(c);
+        self.samples += 1;
+        self.sum += c;
+        let delta = l - self.mean;
+        self.mean += delta / self.samples as f32;
+        self.m2 += delta * (l - self.mean);
+    }
+
+    /// relative standard error of the mean luminance
+    fn error(&self) -> f32 {
+        if self.samples < 2 {
+            return f32::MAX;
+        }
+        let n = self.samples as f32;
+        (self.m2 / (n - 1.0) / n).sqrt() / self.mean.max(1e-2)
+    }
+}
+
+/// adaptively sampled image with the sample count of every pixel
+pub struct AdaptiveImage {
+    /// rendered image
+    pub image: FrameBuffer,
+    /// samples taken per pixel, row major from the top row
+    pub sample_counts: Vec<u32>,
+}
+
+impl AdaptiveImage {
+    /// sample counts as grayscale image, white is the most sampled pixel
+    pub fn sample_count_image(&self) -> image::GrayImage {
+        let max = self.sample_counts.iter().cloned().max().unwrap_or(0).max(1) as f32;
+        let width = self.image.width();
+
+        image::ImageBuffer::from_fn(width, self.image.height(), |x, y| {
+            let n = self.sample_counts[(y * width + x) as usize] as f32;
+            image::Luma([(n / max * 255.0).round() as u8])
+        })
+    }
+}
+
+impl Renderer {
+    /// render with per-pixel adaptive sample counts
+    ///
+    /// uses the same total budget as `render` (`samples` per pixel on average), giving
+    /// extra samples to pixels with the highest estimated error until all pixels converge
+    /// or the budget is spent
+    pub fn render_adaptive(&self, world: &dyn Hitable, camera: &Camera, settings: &AdaptiveSettings) -> AdaptiveImage {
+        let (nx, ny) = (self.width, self.height);
+        let initial = PixelStats { samples: 0, sum: Vec3::zero(), mean: 0.0, m2: 0.0 };
+        let mut stats = vec![initial; (nx * ny) as usize];
+
+        let lights = self.lights(world);
+        let sample = |i: usize, st: &mut PixelStats, n: u32| {
+            let (x, y) = (i as u32 % nx, i as u32 / nx);
+            for _ in 0..n {
+                st.add(self.sample(world, &lights, camera, x, y, st.samples));
+            }
+        };
+
+        let min_samples = settings.min_samples.min(settings.max_samples);
+        stats.par_iter_mut().enumerate().for_each(|(i, st)| sample(i, st, min_samples));
+
+        let budget = u64::from(self.samples) * stats.len() as u64;
+        let mut spent = u64::from(min_samples) * stats.len() as u64;
+        let batch = settings.batch.max(1);
+
+        loop {
+            let mut active = stats.iter()
+                .enumerate()
+                .filter(|(_, st)| st.samples < settings.max_samples)
+                .map(|(i, st)| (i, st.error()))
+                .filter(|&(_, e)| e > settings.threshold)
+                .collect::<Vec<_>>();
+
+            let affordable = ((budget.saturating_sub(spent)) / u64::from(batch)) as usize;
+            if active.is_empty() || affordable == 0 {
+                break;
+            }
+
+            // noisiest pixels first when the budget cannot cover all of them
+            if active.len() > affordable {
+                active.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+                active.truncate(affordable);
+            }
+            let mut selected = vec![false; stats.len()];
+            for &(i, _) in &active {
+                selected[i] = true;
+            }
+
+            stats.par_iter_mut()
+                .enumerate()
+                .filter(|(i, _)| selected[*i])
+                .for_each(|(i, st)| {
+                    let n = batch.min(settings.max_samples - st.samples);
+                    sample(i, st, n)
+                });
+
+            spent = stats.iter().map(|st| u64::from(st.samples)).sum();
+        }
+
+        let mut image = FrameBuffer::new(nx, ny);
+        for (p, st) in image.pixels_mut().iter_mut().zip(stats.iter()) {
+            *p = st.sum / st.samples.max(1) as f32;
+        }
+
+        AdaptiveImage {
+            image,
+            sample_counts: stats.iter().map(|st| st.samples).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ConstantEnvironment, HitableList, Lambertian, Sphere};
+
+    #[test]
+    fn test_adaptive() {
+        // sphere on the left half, flat background on the right
+        let mut world = HitableList::default();
+        world.push(Sphere::new(Vec3::new(-1.0, 0.0, -1.0), 0.9,
+            Box::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.8)))));
+
+        let mut renderer = Renderer::new(16, 8);
+        renderer.samples = 32;
+        renderer.environment = Box::new(ConstantEnvironment::new(Vec3::new(0.5, 0.5, 0.5)));
+        let cam = Camera::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0),
+            90.0, renderer.aspect(), 0.0, 1.0);
+
+        let settings = AdaptiveSettings::new(&renderer, 0.01);
+        let result = renderer.render_adaptive(&world, &cam, &settings);
+
+        let counts = &result.sample_counts;
+        let total = counts.iter().map(|&n| u64::from(n)).sum::<u64>();
+        assert!(total <= 32 * 16 * 8);
+
+        // background converges immediately, the sphere edge keeps sampling
+        assert_eq!(counts[15], settings.min_samples);
+        assert_eq!(result.image.get(15, 0), Vec3::new(0.5, 0.5, 0.5));
+        assert!(counts.iter().any(|&n| n > renderer.samples));
+
+        let again = renderer.render_adaptive(&world, &cam, &settings);
+        assert_eq!(again.image.pixels(), result.image.pixels());
+        assert_eq!(result.sample_count_image().get_pixel(15, 0)[0],
+            (settings.min_samples as f32 / *counts.iter().max().unwrap() as f32 * 255.0).round() as u8);
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use rayon::prelude::*;
+
+use crate::sampler::mix;
+use crate::{srgb_oetf, Camera, Color, FrameBuffer, Hitable, Material, Renderer, Vec3};
+
+/// max number of camera samples averaged into normal and albedo outputs
+const AOV_SAMPLES: u32 = 16;
+
+/// arbitrary output variable, auxiliary per-pixel data of the first camera ray hit
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Aov {
+    /// world space surface normal, zero for misses
+    Normal,
+    /// ray parameter `t` of the hit (distance along the unnormalized ray), zero for misses
+    Depth,
+    /// material surface color, environment radiance for misses
+    Albedo,
+    /// false color identifying the hit material, black for misses
+    Id,
+}
+
+impl Aov {
+    /// all output variables
+    pub const ALL: [Aov; 4] = [Aov::Normal, Aov::Depth, Aov::Albedo, Aov::Id];
+
+    /// lowercase name, also used as file name suffix
+    pub fn name(self) -> &'static str {
+        match self {
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::Albedo => "albedo",
+            Aov::Id => "id",
+        }
+    }
+
+    /// output variable by `name`
+    pub fn from_name(name: &str) -> Option<Aov> {
+        Aov::ALL.iter().cloned().find(|aov| aov.name() == name)
+    }
+}
+
+/// auxiliary output buffers rendered alongside the beauty image
+pub struct Aovs {
+    /// world space normals, averaged over pixel samples
+    pub normal: FrameBuffer,
+    /// hit distance of the first pixel sample in all channels
+    pub depth: FrameBuffer,
+    /// material albedo, averaged over pixel samples
+    pub albedo: FrameBuffer,
+    /// material id color of the first pixel sample
+    pub id: FrameBuffer,
+}
+
+impl Aovs {
+    /// buffer of output variable `aov`
+    pub fn get(&self, aov: Aov) -> &FrameBuffer {
+        match aov {
+            Aov::Normal => &self.normal,
+            Aov::Depth => &self.depth,
+            Aov::Albedo => &self.albedo,
+            Aov::Id => &self.id,
+        }
+    }
+
+    /// save output variable `aov`, format is deduced from path extension
+    ///
+    /// `.exr`, `.hdr` and `.pfm` keep raw values, 8-bit formats store normals remapped to
+    /// [0, 1], depth normalized by the farthest hit and sRGB encoded albedo
+    pub fn save<P: AsRef<Path>>(&self, aov: Aov, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let ext = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        let fb = self.get(aov);
+
+        if let Some("exr") | Some("hdr") | Some("pfm") = ext.as_deref() {
+            return fb.save(path);
+        }
+
+        let max_depth = self.depth.pixels().iter().map(|c| c.x).fold(0.0, f32::max).max(1e-6);
+        let display = |c: Color| match aov {
+            Aov::Normal => c * 0.5 + Vec3::new(0.5, 0.5, 0.5),
+            Aov::Depth => c / max_depth,
+            Aov::Albedo => Vec3::new(srgb_oetf(c.x), srgb_oetf(c.y), srgb_oetf(c.z)),
+            Aov::Id => c,
+        };
+
+        let image: image::RgbImage = image::ImageBuffer::from_fn(fb.width(), fb.height(), |x, y| {
+            let c = display(fb.get(x, y)).clamp(0.0, 1.0) * 255.99;
+            image::Rgb([c.x as u8, c.y as u8, c.z as u8])
+        });
+        image.save(path)
+    }
+}
+
+fn address(material: &dyn Material) -> usize {
+    material as *const dyn Material as *const u8 as usize
+}
+
+/// index of every distinct material of `world` in the order they are collected, keyed by address
+fn material_indices(world: &dyn Hitable) -> HashMap<usize, u64> {
+    let mut materials = Vec::new();
+    world.materials(&mut materials);
+
+    let mut indices = HashMap::new();
+    for material in materials {
+        let next = indices.len() as u64;
+        indices.entry(address(material)).or_insert(next);
+    }
+    indices
+}
+
+/// false color of material `index`, the same in every render of the same scene
+fn material_id(index: u64) -> Color {
+    let h = mix(index);
+    let channel = |shift: u64| ((h >> shift) & 0xff) as f32 / 255.0;
+    Vec3::new(channel(0), channel(8), channel(16))
+}
+
+impl Renderer {
+    /// render auxiliary outputs of the first camera ray hit
+    ///
+    /// camera rays are the same as those of the first samples of `render`
+    pub fn render_aovs(&self, world: &dyn Hitable, camera: &Camera) -> Aovs {
+        let (nx, ny) = (self.width, self.height);
+        let n = self.samples.clamp(1, AOV_SAMPLES);
+        // materials the world does not report share one color
+        let indices = material_indices(world);
+
+        let pixels = (0..nx * ny).into_par_iter()
+            .map(|i| {
+                let (x, y) = (i % nx, i / nx);
+                let (mut normal, mut albedo) = (Vec3::zero(), Vec3::zero());
+                let (mut depth, mut id) = (0.0, Vec3::zero());
+
+                for s in 0..n {
+                    let (ray, _) = self.camera_ray(camera, x, y, s);
+                    match world.hit(&ray, self.t_min, f32::MAX) {
+                        Some(rec) => {
+                            normal += rec.normal;
+                            albedo += rec.material.albedo(&rec);
+                            if s == 0 {
+                                depth = rec.t;
+                                let index = indices.get(&address(rec.material)).cloned();
+                                id = material_id(index.unwrap_or(u64::MAX));
+                            }
+                        }
+                        None => albedo += self.environment.color(ray.direction),
+                    }
+                }
+
+                (normal / n as f32, Vec3::new(depth, depth, depth), albedo / n as f32, id)
+            })
+            .collect::<Vec<_>>();
+
+        let mut aovs = Aovs {
+            normal: FrameBuffer::new(nx, ny),
+            depth: FrameBuffer::new(nx, ny),
+            albedo: FrameBuffer::new(nx, ny),
+            id: FrameBuffer::new(nx, ny),
+        };
+        for (i, (normal, depth, albedo, id)) in pixels.into_iter().enumerate() {
+            aovs.normal.pixels_mut()[i] = normal;
+            aovs.depth.pixels_mut()[i] = depth;
+            aovs.albedo.pixels_mut()[i] = albedo;
+            aovs.id.pixels_mut()[i] = id;
+        }
+
+        aovs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ConstantEnvironment, HitableList, Lambertian, Metal, Sphere};
+
+    #[test]
+    fn test_aovs() {
+        let mut world = HitableList::default();
+        world.push(Sphere::new(Vec3::new(-2.0, 0.0, -3.0), 1.5,
+            Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
+        world.push(Sphere::new(Vec3::new(2.0, 0.0, -3.0), 1.5,
+            Box::new(Metal::new(Vec3::new(0.2, 0.4, 0.6), 0.0))));
+
+        let mut renderer = Renderer::new(16, 8);
+        renderer.samples = 4;
+        renderer.environment = Box::new(ConstantEnvironment::new(Vec3::new(0.1, 0.2, 0.3)));
+        let cam = Camera::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0),
+            90.0, renderer.aspect(), 0.0, 1.0);
+        let aovs = renderer.render_aovs(&world, &cam);
+
+        // pixel near the center of the left sphere
+        let (x, y) = (4, 4);
+        let (ray, _) = renderer.camera_ray(&cam, x, y, 0);
+        let rec = world.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(aovs.depth.get(x, y), Vec3::new(rec.t, rec.t, rec.t));
+        assert!(aovs.normal.get(x, y).z > 0.5);
+        assert!((aovs.albedo.get(x, y) - Vec3::new(0.8, 0.3, 0.3)).norm() < 1e-6);
+        assert!((aovs.albedo.get(12, 4) - Vec3::new(0.2, 0.4, 0.6)).norm() < 1e-6);
+
+        // spheres have different ids, numbered in world order
+        assert_eq!(aovs.id.get(x, y), material_id(0));
+        assert_eq!(aovs.id.get(12, 4), material_id(1));
+        assert_ne!(aovs.id.get(x, y), aovs.id.get(12, 4));
+        assert_eq!(aovs.id.get(x, y), aovs.id.get(x + 1, y));
+
+        // top row center misses
+        assert_eq!(aovs.depth.get(8, 0), Vec3::zero());
+        assert_eq!(aovs.normal.get(8, 0), Vec3::zero());
+        assert_eq!(aovs.albedo.get(8, 0), Vec3::new(0.1, 0.2, 0.3));
+
+        let path = std::env::temp_dir().join("rtw_test_aov_normal.png");
+        aovs.save(Aov::Normal, &path).unwrap();
+        let image = image::open(&path).unwrap().to_rgb();
+        assert_eq!(image.get_pixel(8, 0), &image::Rgb([127, 127, 127]));
+
+        assert_eq!(Aov::from_name("depth"), Some(Aov::Depth));
+        assert_eq!(Aov::from_name("beauty"), None);
+    }
+}
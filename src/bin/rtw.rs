@@ -0,0 +1,299 @@
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::{App, Arg, ArgMatches};
+use rtw::*;
+use rtw::scene::{load_scene, Scene};
+
+fn parse<T: std::str::FromStr>(matches: &ArgMatches, name: &str) -> Option<T> {
+    matches.value_of(name).map(|v| v.parse().unwrap_or_else(|_| {
+        eprintln!("error: invalid value `{}` for --{}", v, name);
+        process::exit(2);
+    }))
+}
+
+/// like `parse`, also rejecting zero
+fn parse_positive(matches: &ArgMatches, name: &str) -> Option<u32> {
+    parse(matches, name).map(|v| match v {
+        0 => {
+            eprintln!("error: invalid value `0` for --{}", name);
+            process::exit(2);
+        }
+        v => v,
+    })
+}
+
+fn load(name: &str, seed: u64) -> Scene {
+    if let Some(scene) = builtin::load(name, seed) {
+        return scene;
+    }
+
+    load_scene(name).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    })
+}
+
+fn save(fb: &FrameBuffer, path: &str, format: Option<&str>, tone_mapping: &ToneMapping) -> std::io::Result<()> {
+    let format = match format {
+        None => return fb.save_with(path, tone_mapping),
+        Some("png") => image::ImageFormat::PNG,
+        Some("jpg") | Some("jpeg") => image::ImageFormat::JPEG,
+        Some("bmp") => image::ImageFormat::BMP,
+        Some("tga") => image::ImageFormat::TGA,
+        Some("pnm") | Some("ppm") => image::ImageFormat::PNM,
+        Some("exr") => return fb.save_exr(path),
+        Some("hdr") => return fb.save_hdr(path),
+        Some("pfm") => return fb.save_pfm(path),
+        Some(f) => unreachable!("unsupported format {}", f),
+    };
+
+    fb.to_image_with(tone_mapping).save_with_format(path, format)
+}
+
+/// `out.png` becomes `out.normal.png`, `format` overrides the extension
+fn aov_path(output: &str, aov: Aov, format: Option<&str>) -> PathBuf {
+    let output = Path::new(output);
+    let stem = output.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+    let ext = format.or_else(|| output.extension().and_then(|e| e.to_str())).unwrap_or("png");
+    output.with_file_name(format!("{}.{}.{}", stem, aov.name(), ext))
+}
+
+fn main() {
+    let scene_help = format!("scene file (.toml) or built-in scene name ({})", builtin::SCENES.join(", "));
+
+    let matches = App::new("rtw")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Ray Tracing in One Weekend renderer")
+        .arg(Arg::with_name("scene").required(true).help(&scene_help))
+        .arg(Arg::with_name("width").long("width").short("W").takes_value(true)
+            .help("image width in pixels"))
+        .arg(Arg::with_name("height").long("height").short("H").takes_value(true)
+            .help("image height in pixels"))
+        .arg(Arg::with_name("spp").long("spp").short("s").takes_value(true)
+            .help("samples per pixel"))
+        .arg(Arg::with_name("depth").long("depth").short("d").takes_value(true)
+            .help("max scatter depth"))
+        .arg(Arg::with_name("threads").long("threads").short("j").takes_value(true)
+            .help("number of render threads (default: all cores)"))
+        .arg(Arg::with_name("no-light-sampling").long("no-light-sampling")
+            .help("find lights only by scattering, disabling next event estimation"))
+        .arg(Arg::with_name("environment").long("environment").takes_value(true)
+            .help("equirectangular environment map (.hdr, .exr) replacing the scene environment"))
+        .arg(Arg::with_name("env-intensity").long("env-intensity").takes_value(true)
+            .help("environment map radiance multiplier"))
+        .arg(Arg::with_name("env-rotation").long("env-rotation").takes_value(true)
+            .help("environment map rotation around y axis in degrees"))
+        .arg(Arg::with_name("tile-size").long("tile-size").takes_value(true)
+            .help("edge length of render tiles in pixels"))
+        .arg(Arg::with_name("tile-order").long("tile-order").takes_value(true)
+            .possible_values(&["spiral", "scanline"])
+            .help("tile scheduling order"))
+        .arg(Arg::with_name("pass-spp").long("pass-spp").takes_value(true)
+            .help("render progressively in passes of this many samples per pixel, writing a preview after each"))
+        .arg(Arg::with_name("checkpoint").long("checkpoint").takes_value(true)
+            .help("save accumulation buffer after each pass and resume from it if it exists"))
+        .arg(Arg::with_name("adaptive").long("adaptive").takes_value(true)
+            .conflicts_with_all(&["pass-spp", "checkpoint"])
+            .help("sample adaptively until the relative error of each pixel is below this threshold"))
+        .arg(Arg::with_name("min-spp").long("min-spp").takes_value(true).requires("adaptive")
+            .help("samples per pixel before adaptive error estimation"))
+        .arg(Arg::with_name("max-spp").long("max-spp").takes_value(true).requires("adaptive")
+            .help("max samples of a single pixel with adaptive sampling"))
+        .arg(Arg::with_name("sample-map").long("sample-map").takes_value(true).requires("adaptive")
+            .help("save per-pixel sample counts of adaptive sampling as grayscale image"))
+        .arg(Arg::with_name("seed").long("seed").takes_value(true).default_value("0")
+            .help("random seed for rendering and built-in scene generation"))
+        .arg(Arg::with_name("exposure").long("exposure").takes_value(true).allow_hyphen_values(true)
+            .help("exposure adjustment in stops"))
+        .arg(Arg::with_name("tonemap").long("tonemap").takes_value(true)
+            .possible_values(&["clamp", "reinhard", "filmic", "aces"])
+            .help("tone mapping operator for 8-bit output"))
+        .arg(Arg::with_name("transfer").long("transfer").takes_value(true)
+            .possible_values(&["gamma2", "srgb"])
+            .help("display transfer function for 8-bit output"))
+        .arg(Arg::with_name("dither").long("dither")
+            .help("apply ordered dithering before 8-bit quantization"))
+        .arg(Arg::with_name("aov").long("aov").takes_value(true).multiple(true).use_delimiter(true)
+            .possible_values(&["normal", "depth", "albedo", "id"])
+            .help("also save auxiliary outputs, named like the output with the output variable before the extension"))
+        .arg(Arg::with_name("denoise").long("denoise").takes_value(true).min_values(0).require_equals(true)
+            .help("denoise output guided by normal, albedo and depth, optionally with strength (default 1)"))
+        .arg(Arg::with_name("output").long("output").short("o").takes_value(true).default_value("out.png")
+            .help("output image path"))
+        .arg(Arg::with_name("format").long("format").takes_value(true)
+            .possible_values(&["png", "jpg", "jpeg", "bmp", "tga", "pnm", "ppm", "exr", "hdr", "pfm"])
+            .help("output image format, exr, hdr and pfm keep linear float radiance (default: deduced from output extension)"))
+        .get_matches();
+
+    if let Some(threads) = parse::<usize>(&matches, "threads") {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global().unwrap();
+    }
+
+    let Scene { world, camera, mut renderer } = load(matches.value_of("scene").unwrap(),
+        parse(&matches, "seed").unwrap());
+
+    if let Some(width) = parse_positive(&matches, "width") {
+        renderer.width = width;
+    }
+    if let Some(height) = parse_positive(&matches, "height") {
+        renderer.height = height;
+    }
+    if let Some(samples) = parse_positive(&matches, "spp") {
+        renderer.samples = samples;
+    }
+    if let Some(depth) = parse(&matches, "depth") {
+        renderer.max_depth = depth;
+    }
+    if let Some(size) = parse(&matches, "tile-size") {
+        renderer.tile_size = size;
+    }
+    match matches.value_of("tile-order") {
+        Some("scanline") => renderer.tile_order = TileOrder::Scanline,
+        Some("spiral") => renderer.tile_order = TileOrder::Spiral,
+        _ => {}
+    }
+    renderer.seed = parse(&matches, "seed").unwrap();
+    if matches.is_present("no-light-sampling") {
+        renderer.light_sampling = false;
+    }
+
+    if let Some(exposure) = parse(&matches, "exposure") {
+        renderer.tone_mapping.exposure = exposure;
+    }
+    match matches.value_of("tonemap") {
+        Some("clamp") => renderer.tone_mapping.operator = ToneOperator::Clamp,
+        Some("reinhard") => renderer.tone_mapping.operator = ToneOperator::Reinhard,
+        Some("filmic") => renderer.tone_mapping.operator = ToneOperator::Filmic,
+        Some("aces") => renderer.tone_mapping.operator = ToneOperator::Aces,
+        _ => {}
+    }
+    match matches.value_of("transfer") {
+        Some("gamma2") => renderer.tone_mapping.transfer = Transfer::Gamma2,
+        Some("srgb") => renderer.tone_mapping.transfer = Transfer::Srgb,
+        _ => {}
+    }
+    if matches.is_present("dither") {
+        renderer.tone_mapping.dither = true;
+    }
+
+    if let Some(path) = matches.value_of("environment") {
+        let mut map = EnvironmentMap::load(path).unwrap_or_else(|e| {
+            eprintln!("error: {}: {}", path, e);
+            process::exit(1);
+        });
+        if let Some(intensity) = parse(&matches, "env-intensity") {
+            map.intensity = intensity;
+        }
+        if let Some(rotation) = parse(&matches, "env-rotation") {
+            map.rotation = rotation;
+        }
+        renderer.environment = Box::new(map);
+    }
+
+    let camera = camera.build(renderer.aspect());
+    let world: Box<dyn Hitable> = match world.bounding_box() {
+        Some(_) => Box::new(BvhNode::new(world)),
+        None => {
+            // keep unbounded objects like planes out of the hierarchy
+            let (bounded, unbounded): (Vec<_>, Vec<_>) = world.into_vec()
+                .into_iter()
+                .partition(|h| h.bounding_box().is_some());
+            let mut world = HitableList::from(unbounded);
+            if !bounded.is_empty() {
+                world.push(BvhNode::from_objects(bounded));
+            }
+            Box::new(world)
+        }
+    };
+
+    let output = matches.value_of("output").unwrap();
+    let aovs = match matches.is_present("aov") || matches.is_present("denoise") {
+        true => Some(renderer.render_aovs(world.as_ref(), &camera)),
+        false => None,
+    };
+    let denoiser = match matches.is_present("denoise") {
+        true => Some(Denoiser::new(parse(&matches, "denoise").unwrap_or(1.0))),
+        false => None,
+    };
+
+    let write = |fb: &FrameBuffer| {
+        let denoised = denoiser.map(|d| d.denoise(fb, aovs.as_ref().unwrap()));
+        if let Err(e) = save(denoised.as_ref().unwrap_or(fb), output, matches.value_of("format"), &renderer.tone_mapping) {
+            eprintln!("error: {}: {}", Path::new(output).display(), e);
+            process::exit(1);
+        }
+    };
+
+    if let (Some(names), Some(aovs)) = (matches.values_of("aov"), &aovs) {
+        for aov in names.filter_map(Aov::from_name) {
+            let path = aov_path(output, aov, matches.value_of("format"));
+            if let Err(e) = aovs.save(aov, &path) {
+                eprintln!("error: {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(threshold) = parse(&matches, "adaptive") {
+        let mut settings = AdaptiveSettings::new(&renderer, threshold);
+        if let Some(n) = parse(&matches, "min-spp") {
+            settings.min_samples = n;
+        }
+        if let Some(n) = parse(&matches, "max-spp") {
+            settings.max_samples = n;
+        }
+
+        let result = renderer.render_adaptive(world.as_ref(), &camera, &settings);
+        let total = result.sample_counts.iter().map(|&n| u64::from(n)).sum::<u64>();
+        eprintln!("{:.1} samples per pixel on average", total as f64 / result.sample_counts.len() as f64);
+        write(&result.image);
+
+        if let Some(path) = matches.value_of("sample-map") {
+            if let Err(e) = result.sample_count_image().save(path) {
+                eprintln!("error: {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let checkpoint = matches.value_of("checkpoint");
+    let pass_samples = match (parse::<u32>(&matches, "pass-spp"), checkpoint) {
+        (Some(n), _) => n.max(1),
+        (None, Some(_)) => 16,
+        (None, None) => {
+            write(&renderer.render(world.as_ref(), &camera));
+            return;
+        }
+    };
+
+    let mut acc = match checkpoint.filter(|path| Path::new(path).exists()) {
+        Some(path) => {
+            let acc = Accumulator::load(path).unwrap_or_else(|e| {
+                eprintln!("error: {}: {}", path, e);
+                process::exit(1);
+            });
+            if !acc.matches(&renderer) {
+                eprintln!("error: {}: checkpoint was rendered with different size or seed", path);
+                process::exit(1);
+            }
+            eprintln!("resuming from {} samples per pixel", acc.samples());
+            acc
+        }
+        None => Accumulator::new(&renderer),
+    };
+
+    renderer.render_progressive(world.as_ref(), &camera, &mut acc, pass_samples, |acc| {
+        eprintln!("{}/{} samples per pixel", acc.samples(), renderer.samples);
+        write(&acc.frame());
+        if let Some(path) = checkpoint {
+            if let Err(e) = acc.save(path) {
+                eprintln!("error: {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    });
+
+    write(&acc.frame());
+}
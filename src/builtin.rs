@@ -0,0 +1,312 @@
+//! built-in scenes of the book chapters
+
+use std::sync::Arc;
+
+use crate::Vec3;
+use crate::Sampler;
+use crate::{CameraParams, ConstantEnvironment, Dielectric, DiffuseLight, HitableList, Lambertian, Metal, Renderer};
+use crate::{Csg, Mat4, Material, Transformed};
+use crate::{Cuboid, MovingSphere, Quad, Sphere};
+use crate::{CheckerTexture, ConstantTexture, MarbleTexture, NoiseTexture};
+use crate::scene::Scene;
+
+/// names of all built-in scenes
+pub const SCENES: &[&str] = &["ch7", "ch8", "ch9", "ch10_11", "ch12", "lights", "textures", "motion", "cornell", "csg"];
+
+/// load built-in scene by name, `seed` drives random scene generation
+pub fn load(name: &str, seed: u64) -> Option<Scene> {
+    let scene = match name {
+        "ch7" => diffuse(),
+        "ch8" => metal(),
+        "ch9" => dielectric(),
+        "ch10_11" => defocus(),
+        "ch12" => random_spheres(11, seed, false),
+        "lights" => lights(),
+        "textures" => textures(seed),
+        "motion" => random_spheres(11, seed, true),
+        "cornell" => cornell(),
+        "csg" => csg(),
+        _ => return None,
+    };
+
+    Some(scene)
+}
+
+fn scene(world: HitableList<'static>, camera: CameraParams) -> Scene {
+    Scene {
+        world,
+        camera,
+        renderer: Renderer::new(200, 100),
+    }
+}
+
+fn default_camera() -> CameraParams {
+    CameraParams {
+        look_from: Vec3::new(0.0, 0.0, 0.0),
+        look_at: Vec3::new(0.0, 0.0, -1.0),
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 90.0,
+        aperture: 0.0,
+        focus_dist: 1.0,
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    }
+}
+
+fn diffuse() -> Scene {
+    let mut list = HitableList::default();
+    list.push(Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5,
+                Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))));
+    list.push(Sphere::new(Vec3::new(0.0, -100.5, -1.0), 100.0,
+                Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))));
+
+    scene(list, default_camera())
+}
+
+fn metal() -> Scene {
+    let mut list = HitableList::default();
+    list.push(Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5,
+                Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
+    list.push(Sphere::new(Vec3::new(0.0, -100.5, -1.0), 100.0,
+                Box::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)))));
+    list.push(Sphere::new(Vec3::new(1.0, 0.0, -1.0), 0.5,
+                Box::new(Metal::new(Vec3::new(0.8, 0.6, 0.2), 0.3))));
+    list.push(Sphere::new(Vec3::new(-1.0, 0.0, -1.0), 0.5,
+                Box::new(Metal::new(Vec3::new(0.8, 0.8, 0.8), 1.0))));
+
+    scene(list, default_camera())
+}
+
+fn glass_spheres() -> HitableList<'static> {
+    let mut list = HitableList::default();
+    list.push(Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5,
+                Box::new(Lambertian::new(Vec3::new(0.1, 0.2, 0.5)))));
+    list.push(Sphere::new(Vec3::new(0.0, -100.5, -1.0), 100.0,
+                Box::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)))));
+    list.push(Sphere::new(Vec3::new(1.0, 0.0, -1.0), 0.5,
+                Box::new(Metal::new(Vec3::new(0.8, 0.6, 0.2), 0.3))));
+    list.push(Sphere::new(Vec3::new(-1.0, 0.0, -1.0), 0.5,
+                Box::new(Dielectric::new(1.5))));
+    list.push(Sphere::new(Vec3::new(-1.0, 0.0, -1.0), -0.45,
+                Box::new(Dielectric::new(1.5))));
+    list
+}
+
+fn dielectric() -> Scene {
+    scene(glass_spheres(), default_camera())
+}
+
+fn defocus() -> Scene {
+    let look_from = Vec3::new(3.0, 3.0, 2.0);
+    let look_at = Vec3::new(0.0, 0.0, -1.0);
+
+    scene(glass_spheres(), CameraParams {
+        look_from,
+        look_at,
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 20.0,
+        aperture: 2.0,
+        focus_dist: (look_from - look_at).norm(),
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    })
+}
+
+/// final scene of the book, diffuse spheres bounce up during the shutter interval if `motion`
+fn random_spheres(n: i32, seed: u64, motion: bool) -> Scene {
+    let mut sampler = Sampler::from_seed(seed);
+    let mut randf = || sampler.next_f32();
+
+    let mut list = HitableList::default();
+
+    list.push(Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0,
+        Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))));
+
+    for i in -n..n {
+        for j in -n..n {
+            let prob = randf();
+            let center = Vec3::new(
+                i as f32 + 0.9 * randf(),
+                0.2,
+                j as f32 + 0.9 * randf(),
+            );
+
+            if (center - Vec3::new(4.0, 0.2, 0.0)).norm() > 0.9 {
+                if prob < 0.8 {
+                    // diffuse
+                    let material = Box::new(Lambertian::new(Vec3::new(
+                        randf() * randf(),
+                        randf() * randf(),
+                        randf() * randf(),
+                    )));
+                    match motion {
+                        true => {
+                            let center1 = center + Vec3::new(0.0, 0.5 * randf(), 0.0);
+                            list.push(MovingSphere::new(center, center1, 0.0, 1.0, 0.2, material));
+                        }
+                        false => list.push(Sphere::new(center, 0.2, material)),
+                    }
+                } else if prob < 0.95 {
+                    // metal
+                    list.push(Sphere::new(center, 0.2,
+                        Box::new(Metal::new(Vec3::new(
+                            0.5 * (1.0 + randf()),
+                            0.5 * (1.0 + randf()),
+                            0.5 * (1.0 + randf()),
+                        ), 0.5 * randf()))));
+                } else {
+                    // glass
+                    list.push(Sphere::new(center, 0.2,
+                        Box::new(Dielectric::new(1.5))));
+                }
+            }
+        }
+    }
+
+    list.push(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0,
+                Box::new(Dielectric::new(1.5))));
+    list.push(Sphere::new(Vec3::new(-4.0, 1.0, 0.0), 1.0,
+                Box::new(Lambertian::new(Vec3::new(0.4, 0.2, 0.1)))));
+    list.push(Sphere::new(Vec3::new(4.0, 1.0, 0.0), 1.0,
+                Box::new(Metal::new(Vec3::new(0.7, 0.6, 0.5), 0.0))));
+
+    scene(list, CameraParams {
+        look_from: Vec3::new(13.0, 2.0, 3.0),
+        look_at: Vec3::new(0.0, 0.0, 0.0),
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 20.0,
+        aperture: 0.1,
+        focus_dist: 10.0,
+        shutter_open: 0.0,
+        shutter_close: if motion { 1.0 } else { 0.0 },
+    })
+}
+
+/// interior scene lit only by spherical lights
+fn lights() -> Scene {
+    let mut list = HitableList::default();
+    list.push(Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0,
+                Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))));
+    list.push(Sphere::new(Vec3::new(0.0, 2.0, 0.0), 2.0,
+                Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
+    list.push(Sphere::new(Vec3::new(4.0, 1.0, 2.0), 1.0,
+                Box::new(Metal::new(Vec3::new(0.8, 0.8, 0.8), 0.1))));
+    list.push(Sphere::new(Vec3::new(0.0, 7.0, 0.0), 1.5,
+                Box::new(DiffuseLight::new(Vec3::new(4.0, 4.0, 4.0)))));
+    list.push(Sphere::new(Vec3::new(-4.0, 1.0, 3.0), 0.7,
+                Box::new(DiffuseLight::new(Vec3::new(6.0, 3.0, 1.0)))));
+
+    let mut scene = scene(list, CameraParams {
+        look_from: Vec3::new(22.0, 4.0, 6.0),
+        look_at: Vec3::new(0.0, 2.0, 0.0),
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 20.0,
+        aperture: 0.0,
+        focus_dist: 10.0,
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    });
+    scene.renderer.environment = Box::new(ConstantEnvironment::new(Vec3::zero()));
+    scene
+}
+
+/// checker ground with perlin noise and marble spheres
+fn textures(seed: u64) -> Scene {
+    let checker = CheckerTexture::new(
+        Arc::new(ConstantTexture::new(Vec3::new(0.2, 0.3, 0.1))),
+        Arc::new(ConstantTexture::new(Vec3::new(0.9, 0.9, 0.9))), 10.0);
+
+    let mut list = HitableList::default();
+    list.push(Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0,
+                Box::new(Lambertian::textured(Arc::new(checker)))));
+    list.push(Sphere::new(Vec3::new(0.0, 2.0, 0.0), 2.0,
+                Box::new(Lambertian::textured(Arc::new(MarbleTexture::new(4.0, seed))))));
+    list.push(Sphere::new(Vec3::new(0.0, 1.0, 3.0), 1.0,
+                Box::new(Lambertian::textured(Arc::new(NoiseTexture::new(4.0, seed))))));
+
+    scene(list, CameraParams {
+        look_from: Vec3::new(13.0, 2.0, 3.0),
+        look_at: Vec3::new(0.0, 1.0, 0.0),
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 30.0,
+        aperture: 0.0,
+        focus_dist: 10.0,
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    })
+}
+
+/// cornell box with a ceiling light, a tall and a short box
+fn cornell() -> Scene {
+    let red: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.65, 0.05, 0.05)));
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.73, 0.73, 0.73)));
+    let green: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.12, 0.45, 0.15)));
+    let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(Vec3::new(15.0, 15.0, 15.0)));
+
+    // walls face into the box
+    let mut list = HitableList::default();
+    list.push(Quad::yz_rect(0.0, 555.0, 0.0, 555.0, 555.0, green).flip());
+    list.push(Quad::yz_rect(0.0, 555.0, 0.0, 555.0, 0.0, red));
+    list.push(Quad::xz_rect(213.0, 343.0, 227.0, 332.0, 554.0, light).flip());
+    list.push(Quad::xz_rect(0.0, 555.0, 0.0, 555.0, 555.0, white.clone()).flip());
+    list.push(Quad::xz_rect(0.0, 555.0, 0.0, 555.0, 0.0, white.clone()));
+    list.push(Quad::xy_rect(0.0, 555.0, 0.0, 555.0, 555.0, white.clone()).flip());
+
+    let rotated = |min: Vec3, max: Vec3, degrees: f32| {
+        let cuboid = Arc::new(Cuboid::new(Vec3::zero(), max - min, white.clone()));
+        let transform = Mat4::translation(min) * Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), degrees);
+        Transformed::new(cuboid, transform)
+    };
+    list.push(rotated(Vec3::new(265.0, 0.0, 295.0), Vec3::new(430.0, 330.0, 460.0), 15.0));
+    list.push(rotated(Vec3::new(130.0, 0.0, 65.0), Vec3::new(295.0, 165.0, 230.0), -18.0));
+
+    let mut scene = scene(list, CameraParams {
+        look_from: Vec3::new(278.0, 278.0, -800.0),
+        look_at: Vec3::new(278.0, 278.0, 0.0),
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 40.0,
+        aperture: 0.0,
+        focus_dist: 10.0,
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    });
+    scene.renderer = Renderer::new(200, 200);
+    scene.renderer.environment = Box::new(ConstantEnvironment::new(Vec3::zero()));
+    scene
+}
+
+/// glass lens, a hollow sphere with a cut-away wedge and a rounded die
+fn csg() -> Scene {
+    let mut list = HitableList::default();
+    list.push(Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0,
+                Box::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))));
+
+    list.push(Csg::intersection(
+        Sphere::new(Vec3::new(-2.5, 1.0, -1.6), 2.0, Box::new(Dielectric::new(1.5))),
+        Sphere::new(Vec3::new(-2.5, 1.0, 1.6), 2.0, Box::new(Dielectric::new(1.5))),
+    ));
+
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.9, 0.9, 0.9)));
+    let shell = Csg::difference(
+        Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))),
+        Sphere::new(Vec3::new(0.0, 1.0, 0.0), 0.8, Box::new(Lambertian::new(Vec3::new(0.9, 0.8, 0.3)))),
+    );
+    list.push(Csg::difference(shell, Cuboid::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.1, 2.1, 1.1), white)));
+
+    let blue = Box::new(Lambertian::new(Vec3::new(0.2, 0.3, 0.8)));
+    let cube = Cuboid::new(Vec3::new(1.8, 0.0, -0.4), Vec3::new(2.8, 1.0, 0.6), Arc::new(Metal::new(Vec3::new(0.8, 0.8, 0.8), 0.2)));
+    list.push(Csg::intersection(cube, Sphere::new(Vec3::new(2.3, 0.5, 0.1), 0.7, blue)));
+
+    let mut scene = scene(list, CameraParams {
+        look_from: Vec3::new(3.0, 3.0, 7.0),
+        look_at: Vec3::new(0.0, 0.8, 0.0),
+        vup: Vec3::new(0.0, 1.0, 0.0),
+        fov: 40.0,
+        aperture: 0.0,
+        focus_dist: 10.0,
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    });
+    scene.renderer = Renderer::new(300, 150);
+    scene
+}
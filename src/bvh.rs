@@ -0,0 +1,188 @@
+use crate::Ray;
+use crate::{Aabb, HitRecord, Hitable, HitableList, Material};
+
+/// bounding volume hierarchy node, built with surface area heuristic
+pub struct BvhNode<'a> {
+    left: Box<dyn Hitable + 'a>,
+    right: Option<Box<dyn Hitable + 'a>>,
+    bbox: Aabb,
+}
+
+impl<'a> BvhNode<'a> {
+    /// build hierarchy from all objects in `list`
+    ///
+    /// panics if `list` is empty or contains unbounded objects
+    pub fn new(list: HitableList<'a>) -> Self {
+        Self::from_objects(list.into_vec())
+    }
+
+    /// build hierarchy from boxed objects
+    ///
+    /// panics if `objects` is empty or contains unbounded objects
+    pub fn from_objects(objects: Vec<Box<dyn Hitable + 'a>>) -> Self {
+        assert!(!objects.is_empty(), "BvhNode requires at least one object");
+
+        let objects = objects.into_iter()
+            .map(|h| {
+                let bbox = h.bounding_box().expect("BvhNode requires bounded objects");
+                (bbox, h)
+            })
+            .collect();
+
+        Self::build(objects)
+    }
+
+    fn build(mut objects: Vec<(Aabb, Box<dyn Hitable + 'a>)>) -> Self {
+        let bbox = objects.iter()
+            .skip(1)
+            .fold(objects[0].0, |acc, (b, _)| Aabb::surrounding(&acc, b));
+
+        if objects.len() <= 2 {
+            let right = objects.pop().unwrap().1;
+            return match objects.pop() {
+                Some((_, left)) => BvhNode { left, right: Some(right), bbox },
+                None => BvhNode { left: right, right: None, bbox },
+            };
+        }
+
+        // sort along longest axis of centroids
+        let centroid_box = objects.iter()
+            .map(|(b, _)| Aabb::new(b.centroid(), b.centroid()))
+            .fold(Aabb::new(objects[0].0.centroid(), objects[0].0.centroid()),
+                |acc, b| Aabb::surrounding(&acc, &b));
+        let axis = centroid_box.longest_axis();
+        objects.sort_by(|(a, _), (b, _)| {
+            a.centroid()[axis].partial_cmp(&b.centroid()[axis]).unwrap()
+        });
+
+        // sweep to find split with minimum surface area cost
+        let n = objects.len();
+        let mut right_area = vec![0.0; n];
+        let mut acc = objects[n - 1].0;
+        for i in (1..n).rev() {
+            acc = Aabb::surrounding(&acc, &objects[i].0);
+            right_area[i] = acc.area();
+        }
+
+        let mut split = n / 2;
+        let mut min_cost = f32::MAX;
+        let mut acc = objects[0].0;
+        for (i, area) in right_area.iter().enumerate().skip(1) {
+            acc = Aabb::surrounding(&acc, &objects[i - 1].0);
+            let cost = i as f32 * acc.area() + (n - i) as f32 * area;
+            if cost < min_cost {
+                min_cost = cost;
+                split = i;
+            }
+        }
+
+        let right = objects.split_off(split);
+        BvhNode {
+            left: Self::build_child(objects),
+            right: Some(Self::build_child(right)),
+            bbox,
+        }
+    }
+
+    fn build_child(mut objects: Vec<(Aabb, Box<dyn Hitable + 'a>)>) -> Box<dyn Hitable + 'a> {
+        match objects.len() {
+            1 => objects.pop().unwrap().1,
+            _ => Box::new(Self::build(objects)),
+        }
+    }
+}
+
+impl<'a> Hitable for BvhNode<'a> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        let left = self.left.hit(ray, t_min, t_max);
+        let t_max = left.as_ref().map_or(t_max, |rec| rec.t);
+        let right = self.right.as_ref().and_then(|h| h.hit(ray, t_min, t_max));
+
+        right.or(left)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        self.left.emitters(lights);
+        if let Some(right) = &self.right {
+            right.emitters(lights);
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        self.left.materials(materials);
+        if let Some(right) = &self.right {
+            right.materials(materials);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Lambertian, Sphere, Vec3};
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn random_vec(rng: &mut StdRng, scale: f32) -> Vec3 {
+        Vec3::new(
+            scale * (2.0 * rng.gen::<f32>() - 1.0),
+            scale * (2.0 * rng.gen::<f32>() - 1.0),
+            scale * (2.0 * rng.gen::<f32>() - 1.0),
+        )
+    }
+
+    fn random_spheres(seed: u64) -> HitableList<'static> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut list = HitableList::default();
+        for _ in 0..300 {
+            list.push(Sphere::new(random_vec(&mut rng, 10.0), 0.1 + rng.gen::<f32>(),
+                Box::new(Lambertian::new(Vec3::unit()))));
+        }
+        list
+    }
+
+    #[test]
+    fn test_same_hits_as_list() {
+        let list = random_spheres(7);
+        let bvh = BvhNode::new(random_spheres(7));
+        assert_eq!(list.bounding_box(), bvh.bounding_box());
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut hits = 0;
+        for _ in 0..2000 {
+            let ray = Ray::new(random_vec(&mut rng, 15.0), random_vec(&mut rng, 1.0));
+
+            let h1 = list.hit(&ray, 0.001, f32::MAX);
+            let h2 = bvh.hit(&ray, 0.001, f32::MAX);
+            match (h1, h2) {
+                (Some(h1), Some(h2)) => {
+                    assert_eq!(h1.t, h2.t);
+                    assert_eq!(h1.point, h2.point);
+                    assert_eq!(h1.normal, h2.normal);
+                    hits += 1;
+                }
+                (None, None) => {}
+                _ => panic!("bvh and list disagree on ray {:?}", (ray.origin, ray.direction)),
+            }
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn test_single_object() {
+        let mut list = HitableList::default();
+        list.push(Sphere::new(Vec3::zero(), 1.0, Box::new(Lambertian::new(Vec3::unit()))));
+        let bvh = BvhNode::new(list);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(bvh.hit(&ray, 0.001, f32::MAX).unwrap().t, 4.0);
+    }
+}
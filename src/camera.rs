@@ -1,6 +1,36 @@
 use crate::Ray;
 use crate::Vec3;
 use crate::random_in_unit_disk;
+use crate::Sampler;
+
+/// camera parameters independent of image aspect ratio
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraParams {
+    /// camera position
+    pub look_from: Vec3,
+    /// point the camera looks at
+    pub look_at: Vec3,
+    /// view up direction
+    pub vup: Vec3,
+    /// vertical field of view in degrees
+    pub fov: f32,
+    /// lens aperture diameter
+    pub aperture: f32,
+    /// distance to focus plane
+    pub focus_dist: f32,
+    /// time the shutter opens
+    pub shutter_open: f32,
+    /// time the shutter closes
+    pub shutter_close: f32,
+}
+
+impl CameraParams {
+    /// construct camera for image with `aspect` ratio (width / height)
+    pub fn build(&self, aspect: f32) -> Camera {
+        Camera::new(self.look_from, self.look_at, self.vup, self.fov, aspect, self.aperture, self.focus_dist)
+            .with_shutter(self.shutter_open, self.shutter_close)
+    }
+}
 
 /// simple camera
 pub struct Camera {
@@ -10,8 +40,9 @@ pub struct Camera {
     vertical: Vec3,
     u: Vec3,
     v: Vec3,
-    w: Vec3,
     lens_radius: f32,
+    time0: f32,
+    time1: f32,
 }
 
 impl Camera {
@@ -29,19 +60,33 @@ impl Camera {
             lower_left_corner: look_from - (half_width * u + half_height * v + w) * focus_dist,
             horizontal: 2.0 * half_width * u * focus_dist,
             vertical: 2.0 * half_height * v * focus_dist,
-            u, v, w,
+            u, v,
             lens_radius: apture / 2.0,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
 
+    /// set shutter interval, camera rays get uniformly distributed times within [`open`, `close`)
+    pub fn with_shutter(mut self, open: f32, close: f32) -> Self {
+        self.time0 = open;
+        self.time1 = close;
+        self
+    }
+
     /// get camera ray, from camera's orgin to uv point on film
-    pub fn get_ray(&self, u: f32, v: f32) -> Ray {
-        let rd = self.lens_radius * random_in_unit_disk();
+    pub fn get_ray(&self, u: f32, v: f32, sampler: &mut Sampler) -> Ray {
+        let rd = self.lens_radius * random_in_unit_disk(sampler);
         let offset = self.u * rd.x + self.v * rd.y;
-        Ray::new(self.origin + offset,
+        let time = match self.time1 > self.time0 {
+            true => self.time0 + sampler.next_f32() * (self.time1 - self.time0),
+            false => self.time0,
+        };
+        Ray::with_time(self.origin + offset,
             self.lower_left_corner
             + u * self.horizontal + v * self.vertical
-            - self.origin - offset
+            - self.origin - offset,
+            time
         )
     }
 }
@@ -0,0 +1,276 @@
+use crate::{Aabb, HitRecord, Hitable, Material, Ray};
+
+/// part of a ray inside a solid, between the boundary hits where it enters and exits
+#[derive(Clone, Copy)]
+pub struct Span<'a> {
+    /// boundary hit entering the solid
+    pub enter: HitRecord<'a>,
+    /// boundary hit leaving the solid
+    pub exit: HitRecord<'a>,
+}
+
+/// hitable object enclosing a volume, with outward normals
+pub trait Solid: Hitable {
+    /// disjoint spans of the whole line of `ray` (any `t`) inside the solid, in order along the ray
+    ///
+    /// by default collects all boundary hits by repeated `hit` and pairs them up,
+    /// which is correct for closed surfaces
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        boundary_spans(self, ray)
+    }
+}
+
+/// spans between consecutive pairs of all boundary hits of `object` along the line of `ray`
+pub(crate) fn boundary_spans<'a, H: Hitable + ?Sized>(object: &'a H, ray: &Ray) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let mut t = -f32::MAX;
+    while let Some(enter) = object.hit(ray, t, f32::MAX) {
+        match object.hit(ray, enter.t, f32::MAX) {
+            Some(exit) => {
+                t = exit.t;
+                spans.push(Span { enter, exit });
+            }
+            None => break,
+        }
+    }
+    spans
+}
+
+/// boolean operation of a `Csg` node
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CsgOp {
+    /// inside either child
+    Union,
+    /// inside both children
+    Intersection,
+    /// inside the first child but not the second
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(self, a: bool, b: bool) -> bool {
+        match self {
+            CsgOp::Union => a || b,
+            CsgOp::Intersection => a && b,
+            CsgOp::Difference => a && !b,
+        }
+    }
+}
+
+/// constructive solid geometry node combining two solids
+///
+/// every boundary keeps the normal, uv and material of the child it comes from,
+/// normals of the subtracted child are flipped to face out of the difference
+pub struct Csg<A, B> {
+    op: CsgOp,
+    a: A,
+    b: B,
+    bbox: Option<Aabb>,
+}
+
+impl<A: Solid, B: Solid> Csg<A, B> {
+    /// construct new node applying `op` to `a` and `b`
+    pub fn new(op: CsgOp, a: A, b: B) -> Self {
+        // box of the union, or conservatively of the first child for the other operations
+        let bbox = match op {
+            CsgOp::Union => a.bounding_box().and_then(|a| Some(Aabb::surrounding(&a, &b.bounding_box()?))),
+            CsgOp::Intersection => a.bounding_box().or_else(|| b.bounding_box()),
+            CsgOp::Difference => a.bounding_box(),
+        };
+        Csg { op, a, b, bbox }
+    }
+
+    /// volume inside `a` or `b`
+    pub fn union(a: A, b: B) -> Self {
+        Self::new(CsgOp::Union, a, b)
+    }
+
+    /// volume inside both `a` and `b`
+    pub fn intersection(a: A, b: B) -> Self {
+        Self::new(CsgOp::Intersection, a, b)
+    }
+
+    /// volume inside `a` with `b` cut away
+    pub fn difference(a: A, b: B) -> Self {
+        Self::new(CsgOp::Difference, a, b)
+    }
+}
+
+impl<A: Solid, B: Solid> Hitable for Csg<A, B> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        if let Some(bbox) = &self.bbox {
+            if !bbox.hit(ray, t_min, t_max) {
+                return None;
+            }
+        }
+
+        self.spans(ray)
+            .into_iter()
+            .flat_map(|span| std::iter::once(span.enter).chain(std::iter::once(span.exit)))
+            .find(|rec| rec.t > t_min && rec.t < t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        self.a.materials(materials);
+        self.b.materials(materials);
+    }
+}
+
+impl<A: Solid, B: Solid> Solid for Csg<A, B> {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        // sweep the boundaries of both children in order, tracking whether the ray is inside each
+        let mut events = Vec::new();
+        for (spans, is_b) in [(self.a.spans(ray), false), (self.b.spans(ray), true)] {
+            for span in spans {
+                events.push((span.enter, is_b, true));
+                events.push((span.exit, is_b, false));
+            }
+        }
+        events.sort_by(|(a, ..), (b, ..)| a.t.partial_cmp(&b.t).unwrap());
+
+        let mut spans = Vec::new();
+        let (mut in_a, mut in_b) = (false, false);
+        let mut enter = None;
+        for (mut rec, is_b, entering) in events {
+            let was_inside = self.op.inside(in_a, in_b);
+            match is_b {
+                true => in_b = entering,
+                false => in_a = entering,
+            }
+            let inside = self.op.inside(in_a, in_b);
+            if inside == was_inside {
+                continue;
+            }
+
+            if is_b && self.op == CsgOp::Difference {
+                rec.normal = -rec.normal;
+            }
+            match inside {
+                true => enter = Some(rec),
+                false => if let Some(enter) = enter.take() {
+                    spans.push(Span { enter, exit: rec });
+                },
+            }
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use crate::{Cylinder, Lambertian, Mat4, Sphere, Torus, Transformed, Vec3};
+
+    fn sphere(z: f32) -> Sphere {
+        Sphere::new(Vec3::new(0.0, 0.0, z), 1.0, Box::new(Lambertian::new(Vec3::new(z, z, z))))
+    }
+
+    /// ray from z = 5 towards -z
+    fn ray() -> Ray {
+        Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0))
+    }
+
+    fn ts(spans: &[Span]) -> Vec<(f32, f32)> {
+        spans.iter().map(|s| (s.enter.t, s.exit.t)).collect()
+    }
+
+    #[test]
+    fn test_spans() {
+        assert_eq!(ts(&sphere(0.0).spans(&ray())), vec![(4.0, 6.0)]);
+        assert!(sphere(0.0).spans(&Ray::new(Vec3::new(5.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0))).is_empty());
+
+        // a ring along its diameter is entered twice
+        let torus = Torus::new(Vec3::zero(), 2.0, 0.5, Box::new(Lambertian::new(Vec3::unit())));
+        let spans = ts(&torus.spans(&Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0))));
+        let expected = [(2.5, 3.5), (6.5, 7.5)];
+        assert_eq!(spans.len(), 2);
+        for (s, e) in spans.iter().zip(&expected) {
+            assert!((s.0 - e.0).abs() < 1e-4 && (s.1 - e.1).abs() < 1e-4, "{:?}", spans);
+        }
+    }
+
+    #[test]
+    fn test_csg() {
+        // a spans z in [-1, 1], b spans z in [0, 2]
+        let union = Csg::union(sphere(0.0), sphere(1.0));
+        assert_eq!(ts(&union.spans(&ray())), vec![(3.0, 6.0)]);
+
+        let lens = Csg::intersection(sphere(0.0), sphere(1.0));
+        let spans = lens.spans(&ray());
+        assert_eq!(ts(&spans), vec![(4.0, 5.0)]);
+        assert_eq!((spans[0].enter.normal, spans[0].exit.normal), (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0)));
+
+        // the cut face has the material of b, albedo 1 against 0 for a
+        let cut = Csg::difference(sphere(0.0), sphere(1.0));
+        let rec = cut.hit(&ray(), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (5.0, Vec3::new(0.0, 0.0, 1.0)));
+        assert_eq!(rec.material.albedo(&rec), Vec3::new(1.0, 1.0, 1.0));
+        let rec = cut.hit(&ray(), 5.0, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (6.0, Vec3::new(0.0, 0.0, -1.0)));
+        assert_eq!(rec.material.albedo(&rec), Vec3::zero());
+
+        // from inside the cut-away part the flipped boundary still faces the ray
+        let inside = Ray::new(Vec3::new(0.0, 0.0, 0.5), Vec3::new(0.0, 0.0, -1.0));
+        let rec = cut.hit(&inside, 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (0.5, Vec3::new(0.0, 0.0, 1.0)));
+
+        assert!(cut.hit(&Ray::new(Vec3::new(3.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)), 0.001, f32::MAX).is_none());
+        let bbox = cut.bounding_box().unwrap();
+        assert_eq!((bbox.min, bbox.max), (Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_nested() {
+        // hollow shell: sphere of radius 2 minus a transformed unit sphere, then cut in half by a third sphere
+        let shell = Csg::difference(
+            Sphere::new(Vec3::zero(), 2.0, Box::new(Lambertian::new(Vec3::unit()))),
+            Transformed::new(Arc::new(sphere(0.0)), Mat4::scaling(Vec3::new(1.0, 1.0, 1.0))),
+        );
+        assert_eq!(ts(&shell.spans(&ray())), vec![(3.0, 4.0), (6.0, 7.0)]);
+
+        let half = Csg::difference(shell, Sphere::new(Vec3::new(0.0, 0.0, 10.0), 10.0,
+            Box::new(Lambertian::new(Vec3::unit()))));
+        let spans = half.spans(&ray());
+        assert_eq!(ts(&spans), vec![(6.0, 7.0)]);
+        assert_eq!(spans[0].enter.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_open() {
+        // tube along z through the unit sphere at the origin, crossed sideways along x
+        let cylinder = |open: bool| {
+            let cylinder = Cylinder::new(Vec3::new(0.0, -3.0, 0.0), 0.5, 6.0, Box::new(Lambertian::new(Vec3::unit())));
+            let cylinder = if open { cylinder.open() } else { cylinder };
+            Transformed::new(Arc::new(cylinder), Mat4::rotation(Vec3::new(1.0, 0.0, 0.0), 90.0))
+        };
+        let across = Ray::new(Vec3::new(-5.0, 0.0, 2.0), Vec3::new(1.0, 0.0, 0.0));
+        let close = |spans: Vec<(f32, f32)>, expected: &[(f32, f32)]| {
+            assert_eq!(spans.len(), expected.len(), "{:?}", spans);
+            for (s, e) in spans.iter().zip(expected) {
+                assert!((s.0 - e.0).abs() < 1e-4 && (s.1 - e.1).abs() < 1e-4, "{:?}", spans);
+            }
+        };
+        close(ts(&cylinder(false).spans(&across)), &[(4.5, 5.5)]);
+        close(ts(&Csg::union(sphere(2.0), cylinder(false)).spans(&across)), &[(4.0, 6.0)]);
+
+        // an open tube encloses nothing, so it neither adds to a union nor cuts a difference
+        assert!(cylinder(true).spans(&across).is_empty());
+        close(ts(&Csg::union(sphere(2.0), cylinder(true)).spans(&across)), &[(4.0, 6.0)]);
+        close(ts(&Csg::difference(sphere(2.0), cylinder(true)).spans(&across)), &[(4.0, 6.0)]);
+        assert!(Csg::intersection(sphere(2.0), cylinder(true)).spans(&across).is_empty());
+        assert!(Csg::union(sphere(-5.0), cylinder(true)).hit(&across, 0.001, f32::MAX).is_none());
+
+        // neither is a partial sweep
+        let across = Ray::new(Vec3::new(0.0, 0.5, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let half = Cylinder::new(Vec3::zero(), 1.0, 1.0, Box::new(Lambertian::new(Vec3::unit()))).with_phi_max(180.0);
+        assert!(half.hit(&across, 0.001, f32::MAX).is_some());
+        assert!(half.spans(&across).is_empty());
+        let full = Cylinder::new(Vec3::zero(), 1.0, 1.0, Box::new(Lambertian::new(Vec3::unit()))).with_phi_max(360.0);
+        assert_eq!(full.spans(&across).len(), 1);
+    }
+}
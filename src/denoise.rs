@@ -0,0 +1,194 @@
+use rayon::prelude::*;
+
+use crate::{luminance, Aovs, FrameBuffer, Vec3};
+
+/// joint bilateral denoiser guided by normal, albedo and depth output variables
+///
+/// lighting is filtered separately from albedo (demodulated), so texture detail in the
+/// albedo buffer stays sharp
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Denoiser {
+    /// filter window radius in pixels
+    pub radius: u32,
+    /// standard deviation of the spatial gaussian in pixels
+    pub sigma_spatial: f32,
+    /// standard deviation of relative luminance differences of filtered lighting
+    pub sigma_color: f32,
+    /// standard deviation of normal differences
+    pub sigma_normal: f32,
+    /// standard deviation of albedo differences
+    pub sigma_albedo: f32,
+    /// standard deviation of relative depth differences
+    pub sigma_depth: f32,
+}
+
+impl Denoiser {
+    /// construct denoiser with `strength`, 0 disables filtering, 1 is a good default
+    pub fn new(strength: f32) -> Self {
+        let strength = strength.max(0.0);
+        let sigma_spatial = 2.0 * strength;
+        Denoiser {
+            radius: (2.0 * sigma_spatial).ceil() as u32,
+            sigma_spatial,
+            sigma_color: strength,
+            sigma_normal: 0.2,
+            sigma_albedo: 0.1,
+            sigma_depth: 0.1,
+        }
+    }
+
+    /// denoise `image` with guide buffers `aovs` of the same size
+    pub fn denoise(&self, image: &FrameBuffer, aovs: &Aovs) -> FrameBuffer {
+        let (w, h) = (image.width(), image.height());
+        assert_eq!((w, h), (aovs.normal.width(), aovs.normal.height()), "output variables size mismatch");
+
+        let mut out = FrameBuffer::new(w, h);
+        if self.radius == 0 || self.sigma_spatial <= 0.0 || self.sigma_color <= 0.0 {
+            out.pixels_mut().copy_from_slice(image.pixels());
+            return out;
+        }
+
+        let albedo = |i: usize| {
+            let a = aovs.albedo.pixels()[i];
+            Vec3::new(a.x.max(1e-3), a.y.max(1e-3), a.z.max(1e-3))
+        };
+        let lighting = (0..image.pixels().len())
+            .map(|i| image.pixels()[i] / albedo(i))
+            .collect::<Vec<_>>();
+
+        let inv = |sigma: f32| -0.5 / (sigma * sigma);
+        let (ks, kc, kn, ka, kd) = (inv(self.sigma_spatial), inv(self.sigma_color),
+            inv(self.sigma_normal), inv(self.sigma_albedo), inv(self.sigma_depth));
+        let r = self.radius as i32;
+
+        out.pixels_mut().par_chunks_mut(w as usize).enumerate().for_each(|(y, row)| {
+            let y = y as i32;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let x = x as i32;
+                let p = (y * w as i32 + x) as usize;
+                let (lp, np, ap, dp) = (lighting[p], aovs.normal.pixels()[p],
+                    aovs.albedo.pixels()[p], aovs.depth.pixels()[p].x);
+                let yp = luminance(lp).max(0.0);
+
+                let mut sum = Vec3::zero();
+                let mut weights = 0.0;
+                for qy in (y - r).max(0)..=(y + r).min(h as i32 - 1) {
+                    for qx in (x - r).max(0)..=(x + r).min(w as i32 - 1) {
+                        let q = (qy * w as i32 + qx) as usize;
+                        let lq = lighting[q];
+                        let (dx, dy) = ((qx - x) as f32, (qy - y) as f32);
+                        let dl = luminance(lq).max(0.0) - yp;
+                        let dd = (aovs.depth.pixels()[q].x - dp) / dp.max(aovs.depth.pixels()[q].x).max(1e-6);
+
+                        let exponent = ks * (dx * dx + dy * dy)
+                            + kc * dl * dl / (yp + luminance(lq).max(0.0) + 1e-2)
+                            + kn * (aovs.normal.pixels()[q] - np).norm_squared()
+                            + ka * (aovs.albedo.pixels()[q] - ap).norm_squared()
+                            + kd * dd * dd;
+                        let weight = exponent.exp();
+                        sum += weight * lq;
+                        weights += weight;
+                    }
+                }
+
+                *pixel = sum / weights * albedo(p);
+            }
+        });
+
+        out
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Sampler;
+
+    fn mse(a: &FrameBuffer, b: &FrameBuffer) -> f32 {
+        let sum = a.pixels().iter().zip(b.pixels())
+            .map(|(&p, &q)| (p - q).norm_squared())
+            .sum::<f32>();
+        sum / (3 * a.pixels().len()) as f32
+    }
+
+    /// two flat regions with different normals, albedo and lighting, split at column 16
+    fn synthetic(noise: f32) -> (FrameBuffer, FrameBuffer, Aovs) {
+        let (w, h) = (32, 16);
+        let mut aovs = Aovs {
+            normal: FrameBuffer::new(w, h),
+            depth: FrameBuffer::new(w, h),
+            albedo: FrameBuffer::new(w, h),
+            id: FrameBuffer::new(w, h),
+        };
+        let mut clean = FrameBuffer::new(w, h);
+        let mut noisy = FrameBuffer::new(w, h);
+        let mut sampler = Sampler::from_seed(7);
+
+        for y in 0..h {
+            for x in 0..w {
+                let left = x < 16;
+                // checker albedo detail on the right
+                let albedo = match (left, (x + y) % 2 == 0) {
+                    (true, _) => Vec3::new(0.8, 0.2, 0.2),
+                    (false, true) => Vec3::new(0.2, 0.8, 0.2),
+                    (false, false) => Vec3::new(0.2, 0.2, 0.8),
+                };
+                let light = if left { 0.5 } else { 2.0 };
+                let normal = if left { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 0.0, 1.0) };
+                let depth = if left { 2.0 } else { 5.0 };
+
+                aovs.normal.set(x, y, normal);
+                aovs.albedo.set(x, y, albedo);
+                aovs.depth.set(x, y, Vec3::new(depth, depth, depth));
+                clean.set(x, y, albedo * light);
+
+                let n = 1.0 + noise * (2.0 * sampler.next_f32() - 1.0);
+                noisy.set(x, y, albedo * light * n);
+            }
+        }
+
+        (clean, noisy, aovs)
+    }
+
+    #[test]
+    fn test_denoise() {
+        let (clean, noisy, aovs) = synthetic(0.8);
+        let denoised = Denoiser::default().denoise(&noisy, &aovs);
+
+        let before = mse(&noisy, &clean);
+        let after = mse(&denoised, &clean);
+        assert!(after < before * 0.2, "{} -> {}", before, after);
+
+        // lighting does not leak across the edge and albedo detail is preserved
+        for &(x, light) in [(15, 0.5), (16, 2.0), (17, 2.0)].iter() {
+            let mean = (0..16).map(|y| luminance(denoised.get(x, y)) / luminance(aovs.albedo.get(x, y)))
+                .sum::<f32>() / 16.0;
+            assert!((mean - light).abs() < 0.1 * light, "column {}: {}", x, mean);
+        }
+        assert!(denoised.get(20, 3).z > 2.0 * denoised.get(20, 3).y);
+        assert!(denoised.get(21, 3).y > 2.0 * denoised.get(21, 3).z);
+
+        // stronger filtering removes more noise
+        let strong = Denoiser::new(2.0).denoise(&noisy, &aovs);
+        assert!(mse(&strong, &clean) < after);
+    }
+
+    #[test]
+    fn test_denoise_clean() {
+        let (clean, noisy, aovs) = synthetic(0.0);
+        assert_eq!(clean.pixels(), noisy.pixels());
+
+        let denoised = Denoiser::default().denoise(&clean, &aovs);
+        assert!(mse(&denoised, &clean) < 1e-10);
+
+        // zero strength is a no-op
+        let (_, noisy, aovs) = synthetic(0.5);
+        assert_eq!(Denoiser::new(0.0).denoise(&noisy, &aovs).pixels(), noisy.pixels());
+    }
+}
@@ -0,0 +1,221 @@
+use std::f32::consts::PI;
+use std::io;
+use std::path::Path;
+
+use crate::{Color, Vec3};
+use crate::texture::load_linear_rgb;
+
+/// environment radiance for rays leaving the scene
+pub trait Environment: Send + Sync {
+    /// radiance arriving from `direction` (not necessarily normalized)
+    fn color(&self, direction: Vec3) -> Color;
+}
+
+/// constant color environment
+#[derive(Clone)]
+pub struct ConstantEnvironment {
+    /// environment radiance
+    pub color: Color,
+}
+
+impl ConstantEnvironment {
+    /// construct new constant environment
+    pub fn new(color: Color) -> Self {
+        ConstantEnvironment { color }
+    }
+}
+
+impl Environment for ConstantEnvironment {
+    fn color(&self, _: Vec3) -> Color {
+        self.color
+    }
+}
+
+/// vertical gradient environment, white to blue sky by default
+#[derive(Clone)]
+pub struct GradientEnvironment {
+    /// radiance looking straight down
+    pub bottom: Color,
+    /// radiance looking straight up
+    pub top: Color,
+}
+
+impl GradientEnvironment {
+    /// construct new gradient environment
+    pub fn new(bottom: Color, top: Color) -> Self {
+        GradientEnvironment { bottom, top }
+    }
+}
+
+impl Default for GradientEnvironment {
+    fn default() -> Self {
+        GradientEnvironment {
+            bottom: Vec3::new(1.0, 1.0, 1.0),
+            top: Vec3::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+impl Environment for GradientEnvironment {
+    fn color(&self, direction: Vec3) -> Color {
+        let unit_dir = direction.normalize();
+        let t = 0.5 * (unit_dir.y + 1.0);
+
+        self.bottom * (1.0 - t) + self.top * t
+    }
+}
+
+/// equirectangular (latitude-longitude) environment map
+///
+/// the top row of the image looks along +y, the center column along -z
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    /// rotation around y axis in degrees
+    pub rotation: f32,
+    /// radiance multiplier
+    pub intensity: f32,
+}
+
+fn invalid_data<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+impl EnvironmentMap {
+    /// construct new environment map from row major `pixels` starting at the top row,
+    /// panics if the map is empty
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "empty environment map");
+        assert_eq!(pixels.len(), width * height, "pixel count mismatch");
+
+        EnvironmentMap {
+            width,
+            height,
+            pixels,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// load Radiance `.hdr` or OpenEXR `.exr` file, other formats are loaded as sRGB 8-bit images
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let ext = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        let (width, height, pixels) = match ext.as_deref() {
+            Some("hdr") => {
+                let reader = io::BufReader::new(std::fs::File::open(path)?);
+                let decoder = image::hdr::HDRDecoder::new(reader).map_err(invalid_data)?;
+                let meta = decoder.metadata();
+                let pixels = decoder.read_image_hdr().map_err(invalid_data)?
+                    .into_iter()
+                    .map(|p| Vec3::new(p[0], p[1], p[2]))
+                    .collect();
+                (meta.width as usize, meta.height as usize, pixels)
+            }
+            Some("exr") => {
+                let image = exr::prelude::read_first_rgba_layer_from_file(path,
+                    |size, _| (size.width(), vec![Vec3::zero(); size.area()]),
+                    |(width, pixels), pos, (r, g, b, _a): (f32, f32, f32, f32)| {
+                        pixels[pos.y() * *width + pos.x()] = Vec3::new(r, g, b);
+                    }).map_err(invalid_data)?;
+                let size = image.layer_data.size;
+                let (_, pixels) = image.layer_data.channel_data.pixels;
+                (size.width(), size.height(), pixels)
+            }
+            _ => load_linear_rgb(path)?,
+        };
+
+        if width == 0 || height == 0 {
+            return Err(invalid_data("empty environment map"));
+        }
+        Ok(Self::new(width, height, pixels))
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn color(&self, direction: Vec3) -> Color {
+        let d = direction.normalize();
+        let phi = d.x.atan2(-d.z) - self.rotation.to_radians();
+        let theta = d.y.clamp(-1.0, 1.0).acos();
+
+        // texel space with bilinear filtering, wrapping horizontally
+        let u = (phi / (2.0 * PI) + 0.5).rem_euclid(1.0) * self.width as f32 - 0.5;
+        let v = (theta / PI * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+
+        let (x0, y0) = (u.floor(), v.floor());
+        let (fx, fy) = (u - x0, v - y0);
+        let x0 = (x0 as isize).rem_euclid(self.width as isize) as usize;
+        let x1 = (x0 + 1) % self.width;
+        let y0 = y0 as usize;
+        let y1 = (y0 + 1).min(self.height - 1);
+
+        let top = self.pixel(x0, y0) * (1.0 - fx) + self.pixel(x1, y0) * fx;
+        let bottom = self.pixel(x0, y1) * (1.0 - fx) + self.pixel(x1, y1) * fx;
+        (top * (1.0 - fy) + bottom * fy) * self.intensity
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gradient() {
+        let env = GradientEnvironment::default();
+        assert_eq!(env.color(Vec3::new(0.0, 2.0, 0.0)), Vec3::new(0.5, 0.7, 1.0));
+        assert_eq!(env.color(Vec3::new(0.0, -1.0, 0.0)), Vec3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_environment_map() {
+        // 4 columns: -z is at the center seam between column 1 and 2
+        let red = Vec3::new(1.0, 0.0, 0.0);
+        let blue = Vec3::new(0.0, 0.0, 1.0);
+        let pixels = vec![blue, red, red, blue, blue, red, red, blue];
+        let mut env = EnvironmentMap::new(4, 2, pixels);
+
+        assert_eq!(env.color(Vec3::new(0.0, 0.0, -1.0)), red);
+        assert_eq!(env.color(Vec3::new(0.0, 0.0, 1.0)), blue);
+
+        env.rotation = 180.0;
+        env.intensity = 2.0;
+        assert!((env.color(Vec3::new(0.0, 0.0, -1.0)) - 2.0 * blue).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = std::env::temp_dir();
+        let color = |x: usize, y: usize| (x as f32, y as f32, 2.0);
+
+        let exr_path = dir.join("rtw_test_environment.exr");
+        exr::prelude::write_rgb_file(&exr_path, 3, 2, color).unwrap();
+        let env = EnvironmentMap::load(&exr_path).unwrap();
+        assert_eq!((env.width, env.height), (3, 2));
+        assert_eq!(env.pixel(2, 1), Vec3::new(2.0, 1.0, 2.0));
+
+        let hdr_path = dir.join("rtw_test_environment.hdr");
+        let pixels = (0..6).map(|i| {
+            let (r, g, b) = color(i % 3, i / 3);
+            image::Rgb([r, g, b])
+        }).collect::<Vec<_>>();
+        let file = std::fs::File::create(&hdr_path).unwrap();
+        image::hdr::HDREncoder::new(file).encode(&pixels, 3, 2).unwrap();
+        let env = EnvironmentMap::load(&hdr_path).unwrap();
+        assert_eq!((env.width, env.height), (3, 2));
+        assert_eq!(env.pixel(2, 1), Vec3::new(2.0, 1.0, 2.0));
+
+        let empty_path = dir.join("rtw_test_environment_empty.hdr");
+        let file = std::fs::File::create(&empty_path).unwrap();
+        image::hdr::HDREncoder::new(file).encode(&[], 0, 0).unwrap();
+        let err = EnvironmentMap::load(&empty_path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
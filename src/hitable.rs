@@ -1,8 +1,11 @@
 use crate::Vec3;
 use crate::Ray;
 use crate::Material;
+use crate::Aabb;
+use crate::Sampler;
 
 /// record for ray object intersection
+#[derive(Clone, Copy)]
 pub struct HitRecord<'a> {
     /// ray distance
     pub t: f32,
@@ -10,14 +13,38 @@ pub struct HitRecord<'a> {
     pub point: Vec3,
     /// surface normal on hit point
     pub normal: Vec3,
+    /// surface u coordinate on hit point
+    pub u: f32,
+    /// surface v coordinate on hit point
+    pub v: f32,
     /// material pointer
     pub material: &'a dyn Material,
 }
 
 /// hitable object trait
-pub trait Hitable: Sync {
+pub trait Hitable: Send + Sync {
     /// test ray object intersection constrained  by `t_min` and `t_max`
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>>;
+
+    /// axis-aligned bounding box of object, `None` if object is unbounded
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// solid angle density of `random_direction` sampling `direction` from `origin`,
+    /// zero if object does not support light sampling
+    fn pdf_value(&self, _origin: Vec3, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    /// random direction from `origin` towards the object
+    fn random_direction(&self, _origin: Vec3, _sampler: &mut Sampler) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    /// collect emissive objects supporting light sampling
+    fn emitters<'s>(&'s self, _lights: &mut Vec<&'s dyn Hitable>) {}
+
+    /// collect materials of all surfaces, always in the same order for the same object
+    fn materials<'s>(&'s self, _materials: &mut Vec<&'s dyn Material>) {}
 }
 
 /// a list of hitable objects
@@ -31,14 +58,59 @@ impl<'a> HitableList<'a> {
     pub fn push<T: 'a + Hitable>(&mut self, hitable: T) {
         self.list.push(Box::new(hitable));
     }
+
+    /// move all objects of `other` into this list
+    pub fn append(&mut self, other: HitableList<'a>) {
+        self.list.extend(other.list);
+    }
+
+    /// number of objects in list
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// whether list contains no object
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// take out all objects
+    pub fn into_vec(self) -> Vec<Box<dyn Hitable + 'a>> {
+        self.list
+    }
+}
+
+impl<'a> From<Vec<Box<dyn Hitable + 'a>>> for HitableList<'a> {
+    fn from(list: Vec<Box<dyn Hitable + 'a>>) -> Self {
+        HitableList { list }
+    }
 }
 
 impl<'a> Hitable for HitableList<'a> {
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
         self.list
             .iter()
             .filter_map(|h| h.hit(ray, t_min, t_max))
             // .filter(|t| !t.is_nan())
             .min_by(|h1, h2| h1.t.partial_cmp(&h2.t).unwrap())
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut iter = self.list.iter().map(|h| h.bounding_box());
+        let first = iter.next()??;
+
+        iter.try_fold(first, |acc, b| Some(Aabb::surrounding(&acc, &b?)))
+    }
+
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        for h in &self.list {
+            h.emitters(lights);
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        for h in &self.list {
+            h.materials(materials);
+        }
+    }
 }
@@ -4,18 +4,59 @@
 pub type Color = vec::Vec3;
 
 pub use ray::Ray;
-pub use vec::{Vec3, reflect, refract};
+pub use vec::{Vec3, Mat4, reflect, refract};
 pub use hitable::*;
-pub use sphere::Sphere;
-pub use camera::Camera;
+pub use aabb::Aabb;
+pub use bvh::BvhNode;
+pub use sphere::{Sphere, MovingSphere};
+pub use motion::Moving;
+pub use transform::Transformed;
+pub use triangle::{Triangle, TriangleMesh, MeshTriangle};
+pub use planar::{Quad, Cuboid, Disk, Plane};
+pub use quadric::{Cylinder, Cone, Paraboloid, Torus};
+pub use csg::{Csg, CsgOp, Solid, Span};
+pub use camera::{Camera, CameraParams};
 pub use material::*;
+pub use renderer::{Renderer, FrameBuffer, Tile, TileOrder};
+pub use sampler::Sampler;
+pub use progressive::Accumulator;
+pub use adaptive::{AdaptiveSettings, AdaptiveImage};
+pub use tonemap::{ToneMapping, ToneOperator, Transfer, srgb_oetf};
+pub use aov::{Aov, Aovs};
+pub use denoise::Denoiser;
+pub use light::{LightList, power_heuristic};
+pub use microfacet::{Conductor, RoughDielectric};
+pub use environment::*;
+pub use texture::*;
 
 mod vec;
 mod ray;
 mod hitable;
+mod aabb;
+mod bvh;
 mod sphere;
+mod motion;
+mod transform;
+mod triangle;
+mod planar;
+mod quadric;
+mod csg;
 mod camera;
 mod material;
+mod microfacet;
+pub mod obj;
+pub mod scene;
+pub mod builtin;
+mod renderer;
+mod sampler;
+mod progressive;
+mod adaptive;
+mod tonemap;
+mod aov;
+mod denoise;
+mod light;
+mod environment;
+mod texture;
 
 /// convert Vec3 to RGB8([u8; 3])
 pub fn vec_to_rgb(c: Color) -> [u8; 3] {
@@ -26,13 +67,15 @@ pub fn vec_to_rgb(c: Color) -> [u8; 3] {
     [c[0] as u8, c[1] as u8, c[2] as u8]
 }
 
-/// generate random point in unit shpere
-pub fn random_in_unit_sphere() -> Vec3 {
-    use rand::prelude::*;
+/// relative luminance of linear RGB color (Rec. 709)
+pub fn luminance(c: Color) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
 
-    let mut rng = rand::thread_rng();
+/// generate random point in unit shpere
+pub fn random_in_unit_sphere(sampler: &mut Sampler) -> Vec3 {
     loop {
-        let p = Vec3::new(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>());
+        let p = Vec3::new(sampler.next_f32(), sampler.next_f32(), sampler.next_f32());
         let p = 2.0 * p - Vec3::unit();
 
         if p.norm_squared() <= 1.0 {
@@ -42,12 +85,9 @@ pub fn random_in_unit_sphere() -> Vec3 {
 }
 
 /// generate random point in unit disk
-pub fn random_in_unit_disk() -> Vec3 {
-    use rand::prelude::*;
-
-    let mut rng = rand::thread_rng();
+pub fn random_in_unit_disk(sampler: &mut Sampler) -> Vec3 {
     loop {
-        let p = Vec3::new(rng.gen::<f32>(), rng.gen::<f32>(), 0.0);
+        let p = Vec3::new(sampler.next_f32(), sampler.next_f32(), 0.0);
         let p = 2.0 * p - Vec3::new(1.0, 1.0, 0.0);
 
         if p.norm_squared() <= 1.0 {
@@ -0,0 +1,85 @@
+use crate::{Hitable, Sampler, Vec3};
+
+/// emissive objects of a scene, sampled uniformly for direct lighting
+pub struct LightList<'a> {
+    lights: Vec<&'a dyn Hitable>,
+}
+
+impl<'a> LightList<'a> {
+    /// collect emitters of `world`
+    pub fn new(world: &'a dyn Hitable) -> Self {
+        let mut lights = Vec::new();
+        world.emitters(&mut lights);
+        LightList { lights }
+    }
+
+    /// list without lights, disabling light sampling
+    pub fn empty() -> Self {
+        LightList { lights: Vec::new() }
+    }
+
+    /// number of lights
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// whether list contains no light
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// solid angle density of `random_direction` sampling `direction` from `origin`
+    pub fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f32 {
+        let sum = self.lights.iter().map(|l| l.pdf_value(origin, direction)).sum::<f32>();
+        sum / self.lights.len().max(1) as f32
+    }
+
+    /// direction from `origin` towards a uniformly chosen light
+    pub fn random_direction(&self, origin: Vec3, sampler: &mut Sampler) -> Vec3 {
+        let n = self.lights.len();
+        let i = ((sampler.next_f32() * n as f32) as usize).min(n - 1);
+        self.lights[i].random_direction(origin, sampler)
+    }
+}
+
+/// power heuristic (beta = 2) weight of a strategy with density `pdf` against `other`
+pub fn power_heuristic(pdf: f32, other: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b > 0.0 { a / (a + b) } else { 0.0 }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DiffuseLight, HitableList, Lambertian, Sphere};
+
+    #[test]
+    fn test_sphere_light_pdf() {
+        let mut world = HitableList::default();
+        world.push(Sphere::new(Vec3::new(0.0, 4.0, 0.0), 1.0, Box::new(DiffuseLight::new(Vec3::unit()))));
+        world.push(Sphere::new(Vec3::new(0.0, -100.0, 0.0), 99.0, Box::new(Lambertian::new(Vec3::unit()))));
+        world.push(Sphere::new(Vec3::new(3.0, 1.0, 0.0), 0.5, Box::new(DiffuseLight::new(Vec3::unit()))));
+
+        let lights = LightList::new(&world);
+        assert_eq!(lights.len(), 2);
+
+        // sampled directions hit a light and the pdf integrates to one over the sphere of directions
+        let origin = Vec3::zero();
+        let mut sampler = Sampler::from_seed(3);
+        for _ in 0..100 {
+            let d = lights.random_direction(origin, &mut sampler);
+            assert!(lights.pdf_value(origin, d) > 0.0);
+        }
+
+        let n = 200_000;
+        let integral = (0..n).map(|_| {
+            let d = crate::random_in_unit_sphere(&mut sampler).normalize();
+            lights.pdf_value(origin, d) * 4.0 * std::f32::consts::PI
+        }).sum::<f32>() / n as f32;
+        assert!((integral - 1.0).abs() < 0.05, "{}", integral);
+
+        assert_eq!(lights.pdf_value(origin, Vec3::new(-1.0, 0.0, 0.0)), 0.0);
+        assert_eq!(power_heuristic(1.0, 0.0), 1.0);
+        assert_eq!(power_heuristic(1.0, 1.0), 0.5);
+    }
+}
@@ -1,11 +1,76 @@
-use crate::{Ray, Vec3, HitRecord};
-use crate::random_in_unit_sphere;
+use std::sync::Arc;
+
+use crate::{Color, Ray, Vec3, HitRecord};
+use crate::{ConstantTexture, Texture};
+use crate::{random_in_unit_sphere, Sampler};
 use crate::{reflect, refract};
 
-/// object material trait
-pub trait Material: Sync {
-    /// material scatters incident ray
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<ScatterRecord>;
+/// object material trait, a BSDF with optional emission
+///
+/// directions are unnormalized, `ray` is the incident ray and `direction` points away from the hit point
+pub trait Material: Send + Sync {
+    /// importance sample scattered direction, `None` if the ray is absorbed
+    fn sample(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<BsdfSample>;
+
+    /// BSDF times cosine for light scattered from `direction` into `ray`,
+    /// zero for specular lobes and by default
+    fn eval(&self, _ray: &Ray, _rec: &HitRecord, _direction: Vec3) -> Color {
+        Vec3::zero()
+    }
+
+    /// solid angle density of `sample` producing `direction`,
+    /// zero for specular lobes and by default
+    fn pdf(&self, _ray: &Ray, _rec: &HitRecord, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    /// scatter incident ray, adapter of `sample` to scattered ray and attenuation
+    fn scatter(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<ScatterRecord> {
+        self.sample(ray, rec, sampler).map(|s| ScatterRecord {
+            scattered: Ray::with_time(rec.point, s.direction, ray.time),
+            attenuation: s.weight,
+        })
+    }
+
+    /// radiance emitted from hit point, black by default
+    fn emitted(&self, _ray: &Ray, _rec: &HitRecord) -> Color {
+        Vec3::zero()
+    }
+
+    /// surface color at hit point for auxiliary outputs, white by default
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Vec3::new(1.0, 1.0, 1.0)
+    }
+
+    /// whether material emits light and its objects should be sampled as lights
+    fn is_emissive(&self) -> bool {
+        false
+    }
+}
+
+/// sampled scattering direction of a BSDF
+#[derive(Debug, Copy, Clone)]
+pub struct BsdfSample {
+    /// scattered direction
+    pub direction: Vec3,
+    /// path throughput `eval / pdf`, attenuation of specular lobes
+    pub weight: Color,
+    /// solid angle density of `direction`, undefined for specular lobes
+    pub pdf: f32,
+    /// sampled lobe is specular (a dirac delta), so `eval` and `pdf` cannot reproduce it
+    pub specular: bool,
+}
+
+impl BsdfSample {
+    /// specular sample in `direction` with `attenuation`
+    pub fn specular(direction: Vec3, attenuation: Color) -> Self {
+        BsdfSample {
+            direction,
+            weight: attenuation,
+            pdf: 0.0,
+            specular: true,
+        }
+    }
 }
 
 /// scatter record with scattered ray and material attenuation
@@ -20,32 +85,56 @@ pub struct ScatterRecord {
 #[derive(Clone)]
 pub struct Lambertian {
     /// lambertian material attenuation
-    pub albedo: Vec3
+    pub albedo: Arc<dyn Texture>
 }
 
 impl Lambertian {
     /// construct new lambertian material
     pub fn new(albedo: Vec3) -> Self {
+        Self::textured(Arc::new(ConstantTexture::new(albedo)))
+    }
+
+    /// construct new lambertian material with textured albedo
+    pub fn textured(albedo: Arc<dyn Texture>) -> Self {
         Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        let target = rec.point + rec.normal + random_in_unit_sphere();
-        
-        Some(ScatterRecord {
-            scattered: Ray::new(rec.point, target - rec.point),
-            attenuation: self.albedo,
+    fn sample(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<BsdfSample> {
+        // normal plus point on unit sphere is cosine distributed
+        let direction = rec.normal + random_in_unit_sphere(sampler).normalize();
+        let pdf = self.pdf(ray, rec, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(BsdfSample {
+            direction,
+            weight: self.albedo.value(rec.u, rec.v, rec.point),
+            pdf,
+            specular: false,
         })
     }
+
+    fn eval(&self, ray: &Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        self.albedo.value(rec.u, rec.v, rec.point) * self.pdf(ray, rec, direction)
+    }
+
+    fn pdf(&self, _: &Ray, rec: &HitRecord, direction: Vec3) -> f32 {
+        (rec.normal.dot(direction) / direction.norm()).max(0.0) / std::f32::consts::PI
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.albedo.value(rec.u, rec.v, rec.point)
+    }
 }
 
 /// metal material, reflecting ray deterministically with attenuation
 #[derive(Clone)]
 pub struct Metal {
     /// metal material attenuation
-    pub albedo: Vec3,
+    pub albedo: Arc<dyn Texture>,
     /// fuzziness of reflected ray
     pub fuzz: f32,
 }
@@ -53,6 +142,11 @@ pub struct Metal {
 impl Metal {
     /// construct new metal material
     pub fn new(albedo: Vec3, fuzz: f32) -> Self {
+        Self::textured(Arc::new(ConstantTexture::new(albedo)), fuzz)
+    }
+
+    /// construct new metal material with textured albedo
+    pub fn textured(albedo: Arc<dyn Texture>, fuzz: f32) -> Self {
         Metal {
             albedo,
             fuzz: fuzz.min(1.0)
@@ -61,19 +155,21 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+    fn sample(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<BsdfSample> {
         let reflected = reflect(ray.direction.normalize(), rec.normal) +
-                        self.fuzz * random_in_unit_sphere();
+                        self.fuzz * random_in_unit_sphere(sampler);
 
+        // fuzzy reflection has no closed form density and is treated as specular
         if reflected.dot(rec.normal) > 0.0 {
-            Some(ScatterRecord {
-                scattered: Ray::new(rec.point, reflected),
-                attenuation: self.albedo,
-            })
+            Some(BsdfSample::specular(reflected, self.albedo.value(rec.u, rec.v, rec.point)))
         } else {
             None
         }
     }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.albedo.value(rec.u, rec.v, rec.point)
+    }
 }
 
 /// dielectric material like glass
@@ -91,9 +187,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        use rand::prelude::*;
-
+    fn sample(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<BsdfSample> {
         let reflected = reflect(ray.direction, rec.normal);
         let attenuation = Vec3::new(1.0, 1.0, 1.0);
 
@@ -109,18 +203,49 @@ impl Material for Dielectric {
         if let Some(refracted) = refract(ray.direction, out_normal, ni_over_nt) {
             let reflect_prob = schlick(cosine, self.ref_index);
 
-            if thread_rng().gen::<f32>() > reflect_prob {
-                return Some(ScatterRecord {
-                    scattered: Ray::new(rec.point, refracted),
-                    attenuation
-                });
+            if sampler.next_f32() > reflect_prob {
+                return Some(BsdfSample::specular(refracted, attenuation));
             }
         }
 
-        Some(ScatterRecord {
-            scattered: Ray::new(rec.point, reflected),
-            attenuation
-        })
+        Some(BsdfSample::specular(reflected, attenuation))
+    }
+}
+
+/// diffuse light material, emitting light without scattering
+#[derive(Clone)]
+pub struct DiffuseLight {
+    /// emitted radiance
+    pub emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    /// construct new diffuse light material
+    pub fn new(emit: Color) -> Self {
+        Self::textured(Arc::new(ConstantTexture::new(emit)))
+    }
+
+    /// construct new diffuse light material with textured emission
+    pub fn textured(emit: Arc<dyn Texture>) -> Self {
+        DiffuseLight { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn sample(&self, _: &Ray, _: &HitRecord, _: &mut Sampler) -> Option<BsdfSample> {
+        None
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+
+    fn emitted(&self, _: &Ray, rec: &HitRecord) -> Color {
+        self.emit.value(rec.u, rec.v, rec.point)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.emit.value(rec.u, rec.v, rec.point).clamp(0.0, 1.0)
     }
 }
 
@@ -131,3 +256,68 @@ fn schlick(cosine: f32, ref_index: f32) -> f32 {
     let r0 = r0 * r0;
     r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(material: &dyn Material) -> HitRecord<'_> {
+        HitRecord {
+            t: 1.0,
+            point: Vec3::zero(),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            u: 0.0,
+            v: 0.0,
+            material,
+        }
+    }
+
+    #[test]
+    fn test_lambertian() {
+        let material = Lambertian::new(Vec3::new(0.5, 0.6, 0.7));
+        let rec = record(&material);
+        let ray = Ray::new(Vec3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        let mut sampler = Sampler::from_seed(1);
+
+        for _ in 0..100 {
+            let s = material.sample(&ray, &rec, &mut sampler).unwrap();
+            assert!(!s.specular);
+            assert!((s.pdf - material.pdf(&ray, &rec, s.direction)).abs() < 1e-6);
+            let weight = material.eval(&ray, &rec, s.direction) / s.pdf;
+            assert!((weight - s.weight).norm() < 1e-5);
+        }
+
+        // reflectance is the albedo: eval integrated over uniformly sampled directions
+        let n = 100_000;
+        let sum = (0..n).map(|_| {
+            let d = random_in_unit_sphere(&mut sampler).normalize();
+            material.eval(&ray, &rec, d) * (4.0 * std::f32::consts::PI)
+        }).sum::<Color>() / n as f32;
+        assert!((sum - Vec3::new(0.5, 0.6, 0.7)).norm() < 0.02, "{:?}", sum);
+
+        assert_eq!(material.eval(&ray, &rec, Vec3::new(0.0, -1.0, 0.0)), Vec3::zero());
+    }
+
+    #[test]
+    fn test_specular() {
+        let metal = Metal::new(Vec3::new(0.9, 0.8, 0.7), 0.0);
+        let rec = record(&metal);
+        let ray = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let mut sampler = Sampler::from_seed(1);
+
+        let s = metal.sample(&ray, &rec, &mut sampler).unwrap();
+        assert!(s.specular);
+        assert!((s.direction - Vec3::new(1.0, 1.0, 0.0).normalize()).norm() < 1e-6);
+        assert_eq!(metal.pdf(&ray, &rec, s.direction), 0.0);
+
+        // scatter adapter
+        let srec = metal.scatter(&ray, &rec, &mut sampler).unwrap();
+        assert_eq!(srec.scattered.origin, rec.point);
+        assert_eq!(srec.attenuation, Vec3::new(0.9, 0.8, 0.7));
+
+        let glass = Dielectric::new(1.5);
+        let s = glass.sample(&ray, &record(&glass), &mut sampler).unwrap();
+        assert!(s.specular);
+        assert_eq!(s.weight, Vec3::new(1.0, 1.0, 1.0));
+    }
+}
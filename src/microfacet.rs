@@ -0,0 +1,444 @@
+use std::f32::consts::PI;
+
+use crate::{BsdfSample, Color, HitRecord, Material, Ray, Sampler, Vec3};
+
+/// shading frame around a unit normal
+struct Frame {
+    s: Vec3,
+    t: Vec3,
+    n: Vec3,
+}
+
+impl Frame {
+    fn new(n: Vec3) -> Self {
+        let (s, t) = n.orthonormal_basis();
+        Frame { s, t, n }
+    }
+
+    fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(v.dot(self.s), v.dot(self.t), v.dot(self.n))
+    }
+
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        v.x * self.s + v.y * self.t + v.z * self.n
+    }
+}
+
+/// GGX (Trowbridge-Reitz) distribution of microfacet normals with width `alpha`
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Ggx {
+    alpha: f32,
+}
+
+impl Ggx {
+    /// distribution from perceptual roughness in [0, 1], `alpha = roughness^2`
+    fn new(roughness: f32) -> Self {
+        Ggx { alpha: (roughness * roughness).clamp(1e-3, 1.0) }
+    }
+
+    /// density of microfacet normal `h`
+    fn d(&self, h: Vec3) -> f32 {
+        if h.z <= 0.0 {
+            return 0.0;
+        }
+        let a2 = self.alpha * self.alpha;
+        let k = h.z * h.z * (a2 - 1.0) + 1.0;
+        a2 / (PI * k * k)
+    }
+
+    fn lambda(&self, w: Vec3) -> f32 {
+        let cos2 = w.z * w.z;
+        if cos2 <= 0.0 {
+            return f32::MAX;
+        }
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        ((1.0 + self.alpha * self.alpha * tan2).sqrt() - 1.0) / 2.0
+    }
+
+    /// masking of direction `w`
+    fn g1(&self, w: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// height correlated masking and shadowing
+    fn g2(&self, wo: Vec3, wi: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// density of visible normal `h` seen from `wo`
+    fn visible_pdf(&self, wo: Vec3, h: Vec3) -> f32 {
+        self.g1(wo) * wo.dot(h).max(0.0) * self.d(h) / wo.z
+    }
+
+    /// sample normal visible from `wo` (upper hemisphere)
+    /// see: Heitz, Sampling the GGX Distribution of Visible Normals
+    fn sample_visible(&self, wo: Vec3, sampler: &mut Sampler) -> Vec3 {
+        let vh = Vec3::new(self.alpha * wo.x, self.alpha * wo.y, wo.z).normalize();
+        let len2 = vh.x * vh.x + vh.y * vh.y;
+        let t1 = match len2 > 0.0 {
+            true => Vec3::new(-vh.y, vh.x, 0.0) / len2.sqrt(),
+            false => Vec3::new(1.0, 0.0, 0.0),
+        };
+        let t2 = vh.cross(t1);
+
+        let r = sampler.next_f32().sqrt();
+        let phi = 2.0 * PI * sampler.next_f32();
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).max(0.0).sqrt() + s * r * phi.sin();
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+
+        Vec3::new(self.alpha * nh.x, self.alpha * nh.y, nh.z.max(1e-6)).normalize()
+    }
+}
+
+fn reflect_local(wo: Vec3, h: Vec3) -> Vec3 {
+    2.0 * wo.dot(h) * h - wo
+}
+
+/// unpolarized fresnel reflectance of conductor with complex index `eta + ik`
+fn fresnel_conductor(cos: f32, eta: f32, k: f32) -> f32 {
+    let c2 = cos * cos;
+    let s2 = 1.0 - c2;
+    let t0 = eta * eta - k * k - s2;
+    let a2b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let t1 = a2b2 + c2;
+    let a = (0.5 * (a2b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos * a;
+    let rs = (t1 - t2) / (t1 + t2);
+    let t3 = c2 * a2b2 + s2 * s2;
+    let t4 = t2 * s2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rp + rs)
+}
+
+/// unpolarized fresnel reflectance of dielectric interface with relative index `eta`
+/// (transmitted over incident side), 1 on total internal reflection
+fn fresnel_dielectric(cos: f32, eta: f32) -> f32 {
+    let sin2_t = (1.0 - cos * cos) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let rs = (cos - eta * cos_t) / (cos + eta * cos_t);
+    let rp = (eta * cos - cos_t) / (eta * cos + cos_t);
+    0.5 * (rs * rs + rp * rp)
+}
+
+/// rough metal with GGX microfacets and complex index of refraction per RGB channel
+#[derive(Debug, Clone)]
+pub struct Conductor {
+    /// real part of index of refraction
+    pub eta: Vec3,
+    /// imaginary part of index of refraction (absorption)
+    pub k: Vec3,
+    /// perceptual roughness in [0, 1]
+    pub roughness: f32,
+}
+
+impl Conductor {
+    /// construct new conductor with complex index `eta + ik`
+    pub fn new(eta: Vec3, k: Vec3, roughness: f32) -> Self {
+        Conductor { eta, k, roughness }
+    }
+
+    /// gold
+    pub fn gold(roughness: f32) -> Self {
+        Self::new(Vec3::new(0.143, 0.374, 1.442), Vec3::new(3.983, 2.385, 1.603), roughness)
+    }
+
+    /// copper
+    pub fn copper(roughness: f32) -> Self {
+        Self::new(Vec3::new(0.200, 0.924, 1.102), Vec3::new(3.912, 2.452, 2.142), roughness)
+    }
+
+    /// aluminium
+    pub fn aluminium(roughness: f32) -> Self {
+        Self::new(Vec3::new(1.657, 0.880, 0.521), Vec3::new(9.224, 6.270, 4.837), roughness)
+    }
+
+    /// preset by name (`gold`, `copper`, `aluminium`)
+    pub fn preset(name: &str, roughness: f32) -> Option<Self> {
+        match name {
+            "gold" => Some(Self::gold(roughness)),
+            "copper" => Some(Self::copper(roughness)),
+            "aluminium" | "aluminum" => Some(Self::aluminium(roughness)),
+            _ => None,
+        }
+    }
+
+    fn fresnel(&self, cos: f32) -> Color {
+        Vec3::new(
+            fresnel_conductor(cos, self.eta.x, self.k.x),
+            fresnel_conductor(cos, self.eta.y, self.k.y),
+            fresnel_conductor(cos, self.eta.z, self.k.z),
+        )
+    }
+
+    /// local frame on the side of the incident ray and outgoing direction
+    fn local(ray: &Ray, rec: &HitRecord) -> (Frame, Vec3) {
+        let wo = -ray.direction.normalize();
+        let n = match wo.dot(rec.normal) >= 0.0 {
+            true => rec.normal,
+            false => -rec.normal,
+        };
+        let frame = Frame::new(n);
+        let wo = frame.to_local(wo);
+        (frame, wo)
+    }
+}
+
+impl Material for Conductor {
+    fn sample(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<BsdfSample> {
+        let (frame, wo) = Self::local(ray, rec);
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        let ggx = Ggx::new(self.roughness);
+        let h = ggx.sample_visible(wo, sampler);
+        let wi = reflect_local(wo, h);
+        if wi.z <= 0.0 {
+            return None;
+        }
+
+        Some(BsdfSample {
+            direction: frame.to_world(wi),
+            weight: self.fresnel(wo.dot(h)) * (ggx.g2(wo, wi) / ggx.g1(wo)),
+            pdf: ggx.visible_pdf(wo, h) / (4.0 * wo.dot(h)),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, ray: &Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let (frame, wo) = Self::local(ray, rec);
+        let wi = frame.to_local(direction.normalize());
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return Vec3::zero();
+        }
+
+        let ggx = Ggx::new(self.roughness);
+        let h = (wo + wi).normalize();
+        self.fresnel(wo.dot(h)) * (ggx.d(h) * ggx.g2(wo, wi) / (4.0 * wo.z))
+    }
+
+    fn pdf(&self, ray: &Ray, rec: &HitRecord, direction: Vec3) -> f32 {
+        let (frame, wo) = Self::local(ray, rec);
+        let wi = frame.to_local(direction.normalize());
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+
+        let ggx = Ggx::new(self.roughness);
+        let h = (wo + wi).normalize();
+        ggx.visible_pdf(wo, h) / (4.0 * wo.dot(h))
+    }
+
+    fn albedo(&self, _: &HitRecord) -> Color {
+        self.fresnel(1.0)
+    }
+}
+
+/// rough glass with GGX microfacets
+///
+/// like `Dielectric`, radiance is not scaled by the squared index ratio on refraction
+#[derive(Debug, Clone)]
+pub struct RoughDielectric {
+    /// refractive index
+    pub ref_index: f32,
+    /// perceptual roughness in [0, 1]
+    pub roughness: f32,
+}
+
+impl RoughDielectric {
+    /// construct new rough dielectric material
+    pub fn new(ref_index: f32, roughness: f32) -> Self {
+        RoughDielectric { ref_index, roughness }
+    }
+
+    /// local frame on the side of the incident ray, outgoing direction and relative index
+    fn local(&self, ray: &Ray, rec: &HitRecord) -> (Frame, Vec3, f32) {
+        let wo = -ray.direction.normalize();
+        let (n, eta) = match wo.dot(rec.normal) >= 0.0 {
+            true => (rec.normal, self.ref_index),
+            false => (-rec.normal, self.ref_index.recip()),
+        };
+        let frame = Frame::new(n);
+        let wo = frame.to_local(wo);
+        (frame, wo, eta)
+    }
+
+    /// microfacet normal, fresnel reflectance and pdf of scattering `wo` to `wi`
+    fn lobe(&self, ggx: &Ggx, wo: Vec3, wi: Vec3, eta: f32) -> Option<(Vec3, f32, f32)> {
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return None;
+        }
+
+        let reflected = wi.z > 0.0;
+        let h = match reflected {
+            true => (wo + wi).normalize(),
+            false => (wo + eta * wi).normalize(),
+        };
+        let h = if h.z < 0.0 { -h } else { h };
+        if wo.dot(h) <= 0.0 || (wi.dot(h) <= 0.0) == reflected {
+            return None;
+        }
+
+        let f = fresnel_dielectric(wo.dot(h), eta);
+        let pdf = match reflected {
+            true => f * ggx.visible_pdf(wo, h) / (4.0 * wo.dot(h)),
+            false => {
+                let denom = wo.dot(h) + eta * wi.dot(h);
+                (1.0 - f) * ggx.visible_pdf(wo, h) * eta * eta * wi.dot(h).abs() / (denom * denom)
+            }
+        };
+        Some((h, f, pdf))
+    }
+}
+
+impl Material for RoughDielectric {
+    fn sample(&self, ray: &Ray, rec: &HitRecord, sampler: &mut Sampler) -> Option<BsdfSample> {
+        let (frame, wo, eta) = self.local(ray, rec);
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        let ggx = Ggx::new(self.roughness);
+        let h = ggx.sample_visible(wo, sampler);
+        let cos = wo.dot(h);
+        let f = fresnel_dielectric(cos, eta);
+
+        let wi = match sampler.next_f32() < f {
+            true => reflect_local(wo, h),
+            false => {
+                let cos_t = (1.0 - (1.0 - cos * cos) / (eta * eta)).max(0.0).sqrt();
+                -wo / eta + (cos / eta - cos_t) * h
+            }
+        };
+
+        let (_, _, pdf) = self.lobe(&ggx, wo, wi, eta)?;
+        Some(BsdfSample {
+            direction: frame.to_world(wi),
+            weight: Vec3::unit() * (ggx.g2(wo, wi) / ggx.g1(wo)),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn eval(&self, ray: &Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let (frame, wo, eta) = self.local(ray, rec);
+        let wi = frame.to_local(direction.normalize());
+        let ggx = Ggx::new(self.roughness);
+
+        match self.lobe(&ggx, wo, wi, eta) {
+            Some((_, _, pdf)) => Vec3::unit() * (pdf * ggx.g2(wo, wi) / ggx.g1(wo)),
+            None => Vec3::zero(),
+        }
+    }
+
+    fn pdf(&self, ray: &Ray, rec: &HitRecord, direction: Vec3) -> f32 {
+        let (frame, wo, eta) = self.local(ray, rec);
+        let wi = frame.to_local(direction.normalize());
+        let ggx = Ggx::new(self.roughness);
+
+        self.lobe(&ggx, wo, wi, eta).map_or(0.0, |(_, _, pdf)| pdf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(material: &dyn Material) -> HitRecord<'_> {
+        HitRecord {
+            t: 1.0,
+            point: Vec3::zero(),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            u: 0.0,
+            v: 0.0,
+            material,
+        }
+    }
+
+    /// sampled weights match eval / pdf, returns mean weight
+    fn check_consistency(material: &dyn Material, ray: &Ray) -> Color {
+        let rec = record(material);
+        let mut sampler = Sampler::from_seed(5);
+        let n = 20_000;
+        let mut sum = Vec3::zero();
+
+        for _ in 0..n {
+            if let Some(s) = material.sample(ray, &rec, &mut sampler) {
+                assert!(!s.specular);
+                let pdf = material.pdf(ray, &rec, s.direction);
+                assert!((pdf - s.pdf).abs() <= 1e-2 * s.pdf, "{} vs {}", pdf, s.pdf);
+                let weight = material.eval(ray, &rec, s.direction) / pdf;
+                assert!((weight - s.weight).norm() <= 1e-2 * s.weight.norm().max(1.0), "{:?} vs {:?}", weight, s.weight);
+                sum += s.weight;
+            }
+        }
+
+        sum / n as f32
+    }
+
+    #[test]
+    fn test_fresnel() {
+        // normal incidence reflectance ((n - 1)^2 + k^2) / ((n + 1)^2 + k^2)
+        let gold = Conductor::gold(0.0);
+        let r = |n: f32, k: f32| ((n - 1.0).powi(2) + k * k) / ((n + 1.0).powi(2) + k * k);
+        let f = gold.fresnel(1.0);
+        assert!((f.x - r(0.143, 3.983)).abs() < 1e-4);
+        assert!((f.z - r(1.442, 1.603)).abs() < 1e-4);
+        // gold is yellow, grazing angles reflect everything
+        assert!(f.x > f.z);
+        assert!(gold.fresnel(1e-4).norm() > 0.99 * 3f32.sqrt());
+
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-6);
+        assert_eq!(fresnel_dielectric(0.3, 1.0 / 1.5), 1.0);
+    }
+
+    #[test]
+    fn test_conductor() {
+        let ray = Ray::new(Vec3::new(-1.0, 2.0, 0.5), Vec3::new(1.0, -2.0, -0.5));
+        for &(roughness, min) in [(0.1, 0.95), (0.5, 0.8), (1.0, 0.3)].iter() {
+            let white = Conductor::new(Vec3::zero(), Vec3::new(1e4, 1e4, 1e4), roughness);
+            let mean = check_consistency(&white, &ray);
+            // energy conserving, rough surfaces lose energy without multiple scattering
+            assert!(mean.x <= 1.0 && mean.x > min, "{}: {:?}", roughness, mean);
+        }
+        check_consistency(&Conductor::copper(0.3), &ray);
+
+        // hits from inside use the flipped normal
+        let gold = Conductor::gold(0.2);
+        let inside = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.2, 1.0, 0.0));
+        let s = gold.sample(&inside, &record(&gold), &mut Sampler::from_seed(1)).unwrap();
+        assert!(s.direction.y < 0.0);
+    }
+
+    #[test]
+    fn test_rough_dielectric() {
+        let glass = RoughDielectric::new(1.5, 0.3);
+        let rec = record(&glass);
+        let mut sampler = Sampler::from_seed(2);
+
+        for ray in [
+            Ray::new(Vec3::new(-1.0, 2.0, 0.5), Vec3::new(1.0, -2.0, -0.5)),
+            // leaving the glass, partly total internal reflection
+            Ray::new(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0)),
+        ].iter() {
+            let mean = check_consistency(&glass, ray);
+            assert!(mean.x <= 1.0 && mean.x > 0.85, "{:?}", mean);
+        }
+
+        // mostly refracts at normal incidence, bending towards the normal
+        let ray = Ray::new(Vec3::new(-0.3, 1.0, 0.0), Vec3::new(0.3, -1.0, 0.0));
+        let refracted = (0..1000)
+            .filter_map(|_| glass.sample(&ray, &rec, &mut sampler))
+            .filter(|s| s.direction.y < 0.0)
+            .collect::<Vec<_>>();
+        assert!(refracted.len() > 900);
+        let mean = refracted.iter().map(|s| s.direction.normalize()).sum::<Vec3>() / refracted.len() as f32;
+        let sin_i = 0.3 / (0.3f32 * 0.3 + 1.0).sqrt();
+        assert!((mean.x / mean.norm() - sin_i / 1.5).abs() < 0.03, "{:?}", mean);
+    }
+}
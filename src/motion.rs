@@ -0,0 +1,102 @@
+use crate::{Aabb, HitRecord, Hitable, Material, Ray, Solid, Span, Vec3};
+
+/// object translated by an offset moving linearly from `offset0` at `time0` to `offset1` at `time1`
+pub struct Moving<H> {
+    object: H,
+    offset0: Vec3,
+    offset1: Vec3,
+    time0: f32,
+    time1: f32,
+}
+
+impl<H: Hitable> Moving<H> {
+    /// construct new moving object, it rests at `offset0` before `time0` and at `offset1` after `time1`
+    pub fn new(object: H, offset0: Vec3, offset1: Vec3, time0: f32, time1: f32) -> Self {
+        Moving {
+            object,
+            offset0,
+            offset1,
+            time0,
+            time1,
+        }
+    }
+
+    /// translation at `time`, always within the bounding box
+    pub fn offset(&self, time: f32) -> Vec3 {
+        if self.time1 == self.time0 {
+            return self.offset0;
+        }
+        let s = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.offset0 + s * (self.offset1 - self.offset0)
+    }
+
+    /// `ray` moved into object space, with the offset to move hits back
+    fn object_ray(&self, ray: &Ray) -> (Ray, Vec3) {
+        let offset = self.offset(ray.time);
+        (Ray::with_time(ray.origin - offset, ray.direction, ray.time), offset)
+    }
+}
+
+impl<H: Hitable> Hitable for Moving<H> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        // intersect the object with the ray moved into object space
+        let (moved, offset) = self.object_ray(ray);
+        self.object.hit(&moved, t_min, t_max).map(|mut rec| {
+            rec.point += offset;
+            rec
+        })
+    }
+
+    /// bounds of the whole motion
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox = self.object.bounding_box()?;
+        let at = |o: Vec3| Aabb::new(bbox.min + o, bbox.max + o);
+        Some(Aabb::surrounding(&at(self.offset0), &at(self.offset1)))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        self.object.materials(materials);
+    }
+}
+
+impl<H: Solid> Solid for Moving<H> {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        let (moved, offset) = self.object_ray(ray);
+        let mut spans = self.object.spans(&moved);
+        for span in &mut spans {
+            span.enter.point += offset;
+            span.exit.point += offset;
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Lambertian, MovingSphere, Sphere};
+
+    #[test]
+    fn test_moving() {
+        let sphere = || Sphere::new(Vec3::new(0.0, 0.0, -2.0), 0.5, Box::new(Lambertian::new(Vec3::unit())));
+        let moving = Moving::new(sphere(), Vec3::zero(), Vec3::new(0.0, 3.0, 0.0), 0.0, 1.0);
+        let reference = MovingSphere::new(Vec3::new(0.0, 0.0, -2.0), Vec3::new(0.0, 3.0, -2.0), 0.0, 1.0, 0.5,
+            Box::new(Lambertian::new(Vec3::unit())));
+
+        for i in 0..20 {
+            let time = i as f32 / 19.0;
+            let ray = Ray::with_time(Vec3::new(0.0, 1.5, 0.0), Vec3::new(0.0, -0.5 + time, -1.0), time);
+            let (a, b) = (moving.hit(&ray, 0.001, f32::MAX), reference.hit(&ray, 0.001, f32::MAX));
+            assert_eq!(a.is_some(), b.is_some());
+            if let (Some(a), Some(b)) = (a, b) {
+                assert!((a.t - b.t).abs() < 1e-5);
+                assert!((a.point - b.point).norm() < 1e-5);
+                assert!((a.normal - b.normal).norm() < 1e-5);
+            }
+        }
+
+        assert_eq!(moving.offset(2.0), Vec3::new(0.0, 3.0, 0.0));
+        let bbox = moving.bounding_box().unwrap();
+        assert_eq!((bbox.min, bbox.max), (Vec3::new(-0.5, -0.5, -2.5), Vec3::new(0.5, 3.5, -1.5)));
+    }
+}
@@ -0,0 +1,415 @@
+//! Wavefront OBJ/MTL loader
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::Vec3;
+use crate::{Dielectric, HitableList, Lambertian, Material, Metal, TriangleMesh};
+
+/// error while loading OBJ or MTL files
+#[derive(Debug)]
+pub enum ObjError {
+    /// failed to read file
+    Io(PathBuf, std::io::Error),
+    /// malformed input at 1-based `line`
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+}
+
+impl ObjError {
+    fn parse<S: Into<String>>(line: usize, message: S) -> Self {
+        ObjError::Parse { path: None, line, message: message.into() }
+    }
+
+    fn with_path(self, p: &Path) -> Self {
+        match self {
+            ObjError::Parse { path: None, line, message } =>
+                ObjError::Parse { path: Some(p.to_owned()), line, message },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ObjError::Parse { path: Some(path), line, message } =>
+                write!(f, "{}:{}: {}", path.display(), line, message),
+            ObjError::Parse { path: None, line, message } =>
+                write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// material parameters parsed from a MTL file
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtlMaterial {
+    /// diffuse color
+    pub kd: Vec3,
+    /// specular color
+    pub ks: Vec3,
+    /// specular exponent
+    pub ns: f32,
+    /// optical density (refractive index)
+    pub ni: f32,
+    /// dissolve (opacity)
+    pub d: f32,
+    /// illumination model
+    pub illum: u32,
+}
+
+#[derive(Debug, PartialEq)]
+enum MtlKind {
+    Diffuse,
+    Metal,
+    Dielectric,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        MtlMaterial {
+            kd: Vec3::new(0.8, 0.8, 0.8),
+            ks: Vec3::zero(),
+            ns: 0.0,
+            ni: 1.5,
+            d: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+impl MtlMaterial {
+    fn kind(&self) -> MtlKind {
+        match self.illum {
+            4 | 6 | 7 | 9 => MtlKind::Dielectric,
+            _ if self.d < 1.0 => MtlKind::Dielectric,
+            3 | 5 => MtlKind::Metal,
+            _ => MtlKind::Diffuse,
+        }
+    }
+
+    /// map onto `Dielectric` when transparent, `Metal` when reflective, otherwise `Lambertian`
+    ///
+    /// metal fuzziness is derived from the specular exponent `Ns`
+    pub fn to_material(&self) -> Arc<dyn Material> {
+        match self.kind() {
+            MtlKind::Dielectric => Arc::new(Dielectric::new(self.ni)),
+            MtlKind::Metal => Arc::new(Metal::new(self.ks, (2.0 / (self.ns + 2.0)).sqrt())),
+            MtlKind::Diffuse => Arc::new(Lambertian::new(self.kd)),
+        }
+    }
+}
+
+/// iterate non-empty, non-comment lines as (1-based line number, keyword, arguments)
+fn statements(source: &str) -> impl Iterator<Item = (usize, &str, Vec<&str>)> {
+    source.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("");
+            let mut tokens = line.split_whitespace();
+            let keyword = tokens.next()?;
+            Some((i + 1, keyword, tokens.collect()))
+        })
+}
+
+fn parse_floats(line: usize, args: &[&str], min: usize, max: usize) -> Result<Vec<f32>, ObjError> {
+    if args.len() < min || args.len() > max {
+        let expected = match min == max {
+            true => min.to_string(),
+            false => format!("{} to {}", min, max),
+        };
+        return Err(ObjError::parse(line, format!("expected {} numbers, found {}", expected, args.len())));
+    }
+
+    args.iter()
+        .map(|a| match a.parse::<f32>() {
+            Ok(f) if f.is_finite() => Ok(f),
+            _ => Err(ObjError::parse(line, format!("invalid number `{}`", a))),
+        })
+        .collect()
+}
+
+fn parse_vec3(line: usize, args: &[&str]) -> Result<Vec3, ObjError> {
+    let f = parse_floats(line, args, 3, 3)?;
+    Ok(Vec3::new(f[0], f[1], f[2]))
+}
+
+/// parse MTL source into named materials
+pub fn parse_mtl(source: &str) -> Result<HashMap<String, MtlMaterial>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for (line, keyword, args) in statements(source) {
+        if keyword == "newmtl" {
+            let name = args.first().ok_or_else(|| ObjError::parse(line, "missing material name"))?;
+            if let Some((name, m)) = current.take() {
+                materials.insert(name, m);
+            }
+            current = Some((name.to_string(), MtlMaterial::default()));
+            continue;
+        }
+
+        let m = match current.as_mut() {
+            Some((_, m)) => m,
+            None => return Err(ObjError::parse(line, format!("`{}` before `newmtl`", keyword))),
+        };
+
+        match keyword {
+            "Kd" => m.kd = parse_vec3(line, &args)?,
+            "Ks" => m.ks = parse_vec3(line, &args)?,
+            "Ns" => m.ns = parse_floats(line, &args, 1, 1)?[0],
+            "Ni" => m.ni = parse_floats(line, &args, 1, 1)?[0],
+            "d" => m.d = parse_floats(line, &args, 1, 1)?[0],
+            "Tr" => m.d = 1.0 - parse_floats(line, &args, 1, 1)?[0],
+            "illum" => m.illum = args.first()
+                .and_then(|a| a.parse().ok())
+                .ok_or_else(|| ObjError::parse(line, "invalid illumination model"))?,
+            // other statements (Ka, Ke, texture maps, ...) are not supported
+            _ => {}
+        }
+    }
+
+    if let Some((name, m)) = current {
+        materials.insert(name, m);
+    }
+
+    Ok(materials)
+}
+
+/// triangle mesh under construction, vertices are de-indexed per (v, vt, vn) triple
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<Vec3>,
+    normals: Vec<Option<Vec3>>,
+    uvs: Vec<Option<[f32; 2]>>,
+    indices: Vec<[usize; 3]>,
+    vertex_map: HashMap<(usize, Option<usize>, Option<usize>), usize>,
+}
+
+impl MeshBuilder {
+    fn build(self, material: Arc<dyn Material>) -> Option<TriangleMesh> {
+        if self.indices.is_empty() {
+            return None;
+        }
+
+        let normals = self.normals.into_iter().collect::<Option<Vec<_>>>();
+        let uvs = self.uvs.into_iter().collect::<Option<Vec<_>>>();
+        Some(TriangleMesh::new(self.positions, normals, uvs, self.indices, material))
+    }
+}
+
+/// resolve 1-based or negative (relative) OBJ index into 0-based index
+fn resolve_index(line: usize, s: &str, len: usize) -> Result<usize, ObjError> {
+    let i = s.parse::<i64>().map_err(|_| ObjError::parse(line, format!("invalid index `{}`", s)))?;
+    let resolved = match i {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => -1,
+    };
+
+    match resolved >= 0 && (resolved as usize) < len {
+        true => Ok(resolved as usize),
+        false => Err(ObjError::parse(line, format!("index {} out of range", i))),
+    }
+}
+
+/// parse OBJ source into triangle meshes, one per group and material
+///
+/// faces are fan triangulated, `usemtl` names are looked up in `materials` and
+/// faces without material use `default_material`
+pub fn parse_obj(source: &str, materials: &HashMap<String, Arc<dyn Material>>,
+                 default_material: Arc<dyn Material>) -> Result<Vec<TriangleMesh>, ObjError> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+
+    let mut meshes = Vec::new();
+    let mut builder = MeshBuilder::default();
+    let mut material = default_material;
+
+    for (line, keyword, args) in statements(source) {
+        match keyword {
+            "v" => positions.push(parse_vec3(line, &args[..args.len().min(3)])?),
+            "vn" => normals.push(parse_vec3(line, &args)?),
+            "vt" => {
+                let f = parse_floats(line, &args, 1, 3)?;
+                uvs.push([f[0], f.get(1).cloned().unwrap_or(0.0)]);
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(ObjError::parse(line, format!("face needs at least 3 vertices, found {}", args.len())));
+                }
+
+                let mut face = Vec::with_capacity(args.len());
+                for arg in &args {
+                    let mut parts = arg.split('/');
+                    let v = resolve_index(line, parts.next().unwrap_or(""), positions.len())?;
+                    let vt = match parts.next() {
+                        Some("") | None => None,
+                        Some(s) => Some(resolve_index(line, s, uvs.len())?),
+                    };
+                    let vn = match parts.next() {
+                        Some("") | None => None,
+                        Some(s) => Some(resolve_index(line, s, normals.len())?),
+                    };
+
+                    let MeshBuilder { positions: p, uvs: t, normals: n, vertex_map, .. } = &mut builder;
+                    let index = *vertex_map.entry((v, vt, vn)).or_insert_with(|| {
+                        p.push(positions[v]);
+                        t.push(vt.map(|i| uvs[i]));
+                        n.push(vn.map(|i| normals[i]));
+                        p.len() - 1
+                    });
+                    face.push(index);
+                }
+
+                for i in 1..face.len() - 1 {
+                    builder.indices.push([face[0], face[i], face[i + 1]]);
+                }
+            }
+            "g" | "o" | "usemtl" => {
+                if keyword == "usemtl" {
+                    let name = args.first().ok_or_else(|| ObjError::parse(line, "missing material name"))?;
+                    let next = materials.get(*name)
+                        .ok_or_else(|| ObjError::parse(line, format!("unknown material `{}`", name)))?
+                        .clone();
+                    meshes.extend(std::mem::take(&mut builder).build(material));
+                    material = next;
+                } else {
+                    meshes.extend(std::mem::take(&mut builder).build(material.clone()));
+                }
+            }
+            // other statements (s, l, mtllib, ...) are handled by the caller or ignored
+            _ => {}
+        }
+    }
+
+    meshes.extend(builder.build(material));
+
+    Ok(meshes)
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+    std::fs::read_to_string(path).map_err(|e| ObjError::Io(path.to_owned(), e))
+}
+
+/// load OBJ file and the MTL files it references into a hitable list
+///
+/// faces without material are rendered as grey `Lambertian`
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<HitableList<'static>, ObjError> {
+    let path = path.as_ref();
+    let source = read(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut materials = HashMap::new();
+    for (_, _, args) in statements(&source).filter(|(_, k, _)| *k == "mtllib") {
+        for name in args {
+            let mtl_path = dir.join(name);
+            let mtl = parse_mtl(&read(&mtl_path)?).map_err(|e| e.with_path(&mtl_path))?;
+            materials.extend(mtl.into_iter().map(|(name, m)| (name, m.to_material())));
+        }
+    }
+
+    let default_material: Arc<dyn Material> = Arc::new(Lambertian::new(MtlMaterial::default().kd));
+    let meshes = parse_obj(&source, &materials, default_material).map_err(|e| e.with_path(path))?;
+
+    let mut list = HitableList::default();
+    for mesh in meshes {
+        list.push(mesh);
+    }
+
+    Ok(list)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Hitable, Ray};
+
+    fn gray() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vec3::unit()))
+    }
+
+    #[test]
+    fn test_parse_obj() {
+        let src = "
+            # unit quad
+            v -1 -1 0
+            v 1 -1 0
+            v 1 1 0
+            v -1 1 0
+            vt 0 0
+            vt 1 0
+            vt 1 1
+            vt 0 1
+            vn 0 0 1
+            g quad
+            f 1/1/1 2/2/1 3/3/1 -1/-1/-1
+            g tri
+            f 1 2 3
+        ";
+        let meshes = parse_obj(src, &HashMap::new(), gray()).unwrap();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].len(), 2);
+        assert_eq!(meshes[1].len(), 1);
+
+        let ray = Ray::new(Vec3::new(-0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = meshes[0].hit(&ray, 0.001, f32::MAX).unwrap();
+        assert!((rec.u - 0.25).abs() < 1e-6);
+        assert!((rec.v - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_obj_errors() {
+        let err = parse_obj("v 0 0 0\nv 1 0\n", &HashMap::new(), gray()).err().unwrap();
+        assert_eq!(err.to_string(), "line 2: expected 3 numbers, found 2");
+
+        let err = parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n", &HashMap::new(), gray()).err().unwrap();
+        assert_eq!(err.to_string(), "line 4: index 4 out of range");
+
+        let err = parse_obj("usemtl glass\n", &HashMap::new(), gray()).err().unwrap();
+        assert_eq!(err.to_string(), "line 1: unknown material `glass`");
+
+        let err = parse_obj("v 0 0 x\n", &HashMap::new(), gray()).err().unwrap();
+        assert_eq!(err.to_string(), "line 1: invalid number `x`");
+
+        let err = parse_obj("v 0 0 0\nv nan 0 0\n", &HashMap::new(), gray()).err().unwrap();
+        assert_eq!(err.to_string(), "line 2: invalid number `nan`");
+
+        let err = parse_obj("v inf 0 0\n", &HashMap::new(), gray()).err().unwrap();
+        assert_eq!(err.to_string(), "line 1: invalid number `inf`");
+    }
+
+    #[test]
+    fn test_parse_mtl() {
+        let src = "
+            newmtl red
+            Kd 0.8 0.1 0.1
+            newmtl gold
+            Ks 1.0 0.8 0.3
+            Ns 200
+            illum 3
+            newmtl glass
+            Ni 1.45
+            d 0.2
+        ";
+        let m = parse_mtl(src).unwrap();
+        assert_eq!(m.len(), 3);
+        assert_eq!(m["red"].kd, Vec3::new(0.8, 0.1, 0.1));
+        assert_eq!(m["red"].kind(), MtlKind::Diffuse);
+        assert_eq!(m["gold"].kind(), MtlKind::Metal);
+        assert_eq!(m["glass"].kind(), MtlKind::Dielectric);
+        assert_eq!(m["glass"].ni, 1.45);
+
+        let err = parse_mtl("Kd 1 1 1\n").err().unwrap();
+        assert_eq!(err.to_string(), "line 1: `Kd` before `newmtl`");
+    }
+}
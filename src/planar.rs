@@ -0,0 +1,396 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::{Aabb, HitRecord, Hitable, Material, Ray, Sampler, Solid, Vec3};
+
+/// parallelogram spanned by edges `u` and `v` from corner `q`
+pub struct Quad {
+    q: Vec3,
+    u: Vec3,
+    v: Vec3,
+    /// `n / (n·n)` with `n = u × v`, projects hit points to planar coordinates
+    w: Vec3,
+    normal: Vec3,
+    area: f32,
+    material: Arc<dyn Material>,
+}
+
+impl Quad {
+    /// construct new quad, the normal is `u × v` and uv coordinates go along `u` and `v`
+    pub fn new(q: Vec3, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Self {
+        let n = u.cross(v);
+        Quad {
+            q,
+            u,
+            v,
+            w: n / n.dot(n),
+            normal: n.normalize(),
+            area: n.norm(),
+            material,
+        }
+    }
+
+    /// rectangle [`x0`, `x1`] x [`y0`, `y1`] at z = `k` facing +z
+    pub fn xy_rect(x0: f32, x1: f32, y0: f32, y1: f32, k: f32, material: Arc<dyn Material>) -> Self {
+        Quad::new(Vec3::new(x0, y0, k), Vec3::new(x1 - x0, 0.0, 0.0), Vec3::new(0.0, y1 - y0, 0.0), material)
+    }
+
+    /// rectangle [`x0`, `x1`] x [`z0`, `z1`] at y = `k` facing +y
+    pub fn xz_rect(x0: f32, x1: f32, z0: f32, z1: f32, k: f32, material: Arc<dyn Material>) -> Self {
+        Quad::new(Vec3::new(x0, k, z0), Vec3::new(x1 - x0, 0.0, 0.0), Vec3::new(0.0, 0.0, z1 - z0), material)
+            .flip()
+    }
+
+    /// rectangle [`y0`, `y1`] x [`z0`, `z1`] at x = `k` facing +x
+    pub fn yz_rect(y0: f32, y1: f32, z0: f32, z1: f32, k: f32, material: Arc<dyn Material>) -> Self {
+        Quad::new(Vec3::new(k, y0, z0), Vec3::new(0.0, y1 - y0, 0.0), Vec3::new(0.0, 0.0, z1 - z0), material)
+    }
+
+    /// same quad facing the other way, keeping uv coordinates
+    pub fn flip(mut self) -> Self {
+        self.normal = -self.normal;
+        self
+    }
+}
+
+impl Hitable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let t = plane_t(self.q, self.normal, ray, t_min, t_max)?;
+        let point = ray.point_at(t);
+
+        let h = point - self.q;
+        let (u, v) = (self.w.dot(h.cross(self.v)), self.w.dot(self.u.cross(h)));
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+
+        Some(HitRecord {
+            t,
+            point,
+            normal: self.normal,
+            u,
+            v,
+            material: self.material.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = [self.q + self.u, self.q + self.v, self.q + self.u + self.v];
+        let bbox = corners.iter().fold(Aabb::new(self.q, self.q), |b, &p| Aabb::surrounding(&b, &Aabb::new(p, p)));
+        Some(padded(bbox))
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f32 {
+        match self.hit(&Ray::new(origin, direction), 0.001, f32::MAX) {
+            Some(rec) => area_pdf(rec.t, direction, self.normal, self.area),
+            None => 0.0,
+        }
+    }
+
+    fn random_direction(&self, origin: Vec3, sampler: &mut Sampler) -> Vec3 {
+        self.q + sampler.next_f32() * self.u + sampler.next_f32() * self.v - origin
+    }
+
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.material.as_ref());
+    }
+}
+
+/// axis-aligned box made of six quads facing outward
+pub struct Cuboid {
+    faces: [Quad; 6],
+    bbox: Aabb,
+}
+
+impl Cuboid {
+    /// construct new box between corners `min` and `max`
+    pub fn new(min: Vec3, max: Vec3, material: Arc<dyn Material>) -> Self {
+        let m = || material.clone();
+        Cuboid {
+            faces: [
+                Quad::xy_rect(min.x, max.x, min.y, max.y, max.z, m()),
+                Quad::xy_rect(min.x, max.x, min.y, max.y, min.z, m()).flip(),
+                Quad::xz_rect(min.x, max.x, min.z, max.z, max.y, m()),
+                Quad::xz_rect(min.x, max.x, min.z, max.z, min.y, m()).flip(),
+                Quad::yz_rect(min.y, max.y, min.z, max.z, max.x, m()),
+                Quad::yz_rect(min.y, max.y, min.z, max.z, min.x, m()).flip(),
+            ],
+            bbox: Aabb::new(min, max),
+        }
+    }
+}
+
+impl Hitable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        self.faces.iter().fold(None, |closest, face| {
+            let t_max = closest.as_ref().map_or(t_max, |rec: &HitRecord| rec.t);
+            face.hit(ray, t_min, t_max).or(closest)
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(padded(self.bbox))
+    }
+
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        for face in &self.faces {
+            face.emitters(lights);
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        for face in &self.faces {
+            face.materials(materials);
+        }
+    }
+}
+
+impl Solid for Cuboid {}
+
+/// disk with `center`, facing `normal`
+pub struct Disk {
+    center: Vec3,
+    normal: Vec3,
+    radius: f32,
+    material: Arc<dyn Material>,
+}
+
+impl Disk {
+    /// construct new disk, u goes around the center and v from center to rim
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: Arc<dyn Material>) -> Self {
+        Disk {
+            center,
+            normal: normal.normalize(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hitable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let t = plane_t(self.center, self.normal, ray, t_min, t_max)?;
+        let point = ray.point_at(t);
+        let d = point - self.center;
+        if d.norm_squared() > self.radius * self.radius {
+            return None;
+        }
+
+        let (s, t_axis) = self.normal.orthonormal_basis();
+        let phi = d.dot(t_axis).atan2(d.dot(s));
+
+        Some(HitRecord {
+            t,
+            point,
+            normal: self.normal,
+            u: (phi + PI) / (2.0 * PI),
+            v: d.norm() / self.radius,
+            material: self.material.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let n = self.normal;
+        let extent = |a: f32| self.radius * (1.0 - a * a).max(0.0).sqrt();
+        let e = Vec3::new(extent(n.x), extent(n.y), extent(n.z));
+        Some(padded(Aabb::new(self.center - e, self.center + e)))
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f32 {
+        match self.hit(&Ray::new(origin, direction), 0.001, f32::MAX) {
+            Some(rec) => area_pdf(rec.t, direction, self.normal, PI * self.radius * self.radius),
+            None => 0.0,
+        }
+    }
+
+    fn random_direction(&self, origin: Vec3, sampler: &mut Sampler) -> Vec3 {
+        // uniform in area
+        let r = self.radius * sampler.next_f32().sqrt();
+        let phi = 2.0 * PI * sampler.next_f32();
+        self.center + self.normal.from_local(Vec3::new(r * phi.cos(), r * phi.sin(), 0.0)) - origin
+    }
+
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.material.as_ref());
+    }
+}
+
+/// infinite plane through `point`, facing `normal`
+pub struct Plane {
+    point: Vec3,
+    normal: Vec3,
+    material: Arc<dyn Material>,
+}
+
+impl Plane {
+    /// construct new plane, uv coordinates are distances from `point` along the plane
+    pub fn new(point: Vec3, normal: Vec3, material: Arc<dyn Material>) -> Self {
+        Plane {
+            point,
+            normal: normal.normalize(),
+            material,
+        }
+    }
+}
+
+impl Hitable for Plane {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let t = plane_t(self.point, self.normal, ray, t_min, t_max)?;
+        let point = ray.point_at(t);
+        let (s, t_axis) = self.normal.orthonormal_basis();
+        let d = point - self.point;
+
+        Some(HitRecord {
+            t,
+            point,
+            normal: self.normal,
+            u: d.dot(s),
+            v: d.dot(t_axis),
+            material: self.material.as_ref(),
+        })
+    }
+
+    /// unbounded
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.material.as_ref());
+    }
+}
+
+/// ray distance to plane through `p` with `normal` in (`t_min`, `t_max`), `None` if parallel
+fn plane_t(p: Vec3, normal: Vec3, ray: &Ray, t_min: f32, t_max: f32) -> Option<f32> {
+    let denom = normal.dot(ray.direction);
+    if denom.abs() < 1e-8 {
+        return None;
+    }
+
+    let t = normal.dot(p - ray.origin) / denom;
+    if t < t_max && t > t_min { Some(t) } else { None }
+}
+
+/// solid angle density of uniformly sampling a planar light of `area`, seen at distance `t` along `direction`
+fn area_pdf(t: f32, direction: Vec3, normal: Vec3, area: f32) -> f32 {
+    let dist_squared = t * t * direction.norm_squared();
+    let cos = normal.dot(direction).abs() / direction.norm();
+    dist_squared / (cos * area)
+}
+
+/// box thickened along flat axes so the slab test can hit it
+fn padded(bbox: Aabb) -> Aabb {
+    let mut pad = Vec3::zero();
+    for a in 0..3 {
+        if bbox.max[a] - bbox.min[a] < 1e-4 {
+            pad[a] = 1e-4;
+        }
+    }
+    Aabb::new(bbox.min - pad, bbox.max + pad)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{random_in_unit_sphere, DiffuseLight, Lambertian};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vec3::unit()))
+    }
+
+    fn down(x: f32, z: f32) -> Ray {
+        Ray::new(Vec3::new(x, 5.0, z), Vec3::new(0.0, -1.0, 0.0))
+    }
+
+    #[test]
+    fn test_quad() {
+        let quad = Quad::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.0, 0.0, -1.0), material());
+        let rec = quad.hit(&down(1.5, -0.5), 0.001, f32::MAX).unwrap();
+        assert_eq!(rec.t, 5.0);
+        assert_eq!(rec.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!((rec.u - 0.5).abs() < 1e-6 && (rec.v - 0.5).abs() < 1e-6);
+        assert!(quad.hit(&down(0.2, -0.5), 0.001, f32::MAX).is_none());
+        assert!(quad.hit(&Ray::new(Vec3::new(0.5, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.001, f32::MAX).is_none());
+
+        let rects = [
+            (Quad::xy_rect(0.0, 1.0, 0.0, 1.0, 0.0, material()), Vec3::new(0.0, 0.0, 1.0)),
+            (Quad::xz_rect(0.0, 1.0, 0.0, 1.0, 0.0, material()), Vec3::new(0.0, 1.0, 0.0)),
+            (Quad::yz_rect(0.0, 1.0, 0.0, 1.0, 0.0, material()), Vec3::new(1.0, 0.0, 0.0)),
+        ];
+        for (rect, normal) in &rects {
+            let rec = rect.hit(&Ray::new(Vec3::new(0.25, 0.25, 0.25) + *normal, -*normal), 0.001, f32::MAX).unwrap();
+            assert_eq!(rec.normal, *normal);
+            assert!((rec.u - 0.25).abs() < 1e-6 && (rec.v - 0.25).abs() < 1e-6);
+
+            let bbox = rect.bounding_box().unwrap();
+            assert!(bbox.hit(&Ray::new(Vec3::new(0.5, 0.5, 0.5) + *normal, -*normal), 0.001, f32::MAX));
+        }
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let cuboid = Cuboid::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 2.0, 3.0), material());
+        let rec = cuboid.hit(&down(0.0, 0.0), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (3.0, Vec3::new(0.0, 1.0, 0.0)));
+
+        // from inside, the far face is hit with its outward normal
+        let rec = cuboid.hit(&Ray::new(Vec3::zero(), Vec3::new(-1.0, 0.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (1.0, Vec3::new(-1.0, 0.0, 0.0)));
+        let rec = cuboid.hit(&Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (3.0, Vec3::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_disk_and_plane() {
+        let disk = Disk::new(Vec3::zero(), Vec3::new(0.0, 2.0, 0.0), 1.0, material());
+        let rec = disk.hit(&down(0.6, 0.0), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (5.0, Vec3::new(0.0, 1.0, 0.0)));
+        assert!((rec.v - 0.6).abs() < 1e-6);
+        assert!(disk.hit(&down(0.8, 0.8), 0.001, f32::MAX).is_none());
+        let bbox = disk.bounding_box().unwrap();
+        assert!((bbox.max - Vec3::new(1.0, 1e-4, 1.0)).norm() < 1e-6);
+
+        let plane = Plane::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), material());
+        assert_eq!(plane.hit(&down(1e4, -1e4), 0.001, f32::MAX).unwrap().t, 6.0);
+        assert!(plane.bounding_box().is_none());
+    }
+
+    #[test]
+    fn test_light_pdf() {
+        let light = || -> Arc<dyn Material> { Arc::new(DiffuseLight::new(Vec3::unit())) };
+        let quad = Quad::xz_rect(-1.0, 1.0, -0.5, 0.5, 2.0, light()).flip();
+        let disk = Disk::new(Vec3::new(0.0, 2.0, 1.0), Vec3::new(0.0, -1.0, -1.0), 1.0, light());
+        let mut sampler = Sampler::from_seed(1);
+
+        for object in [&quad as &dyn Hitable, &disk] {
+            let mut lights = Vec::new();
+            object.emitters(&mut lights);
+            assert_eq!(lights.len(), 1);
+
+            for _ in 0..10 {
+                let d = object.random_direction(Vec3::zero(), &mut sampler);
+                assert!(object.hit(&Ray::new(Vec3::zero(), d), 0.001, f32::MAX).is_some());
+            }
+
+            // density integrates to one over the sphere of directions
+            let n = 200_000;
+            let sum = (0..n).map(|_| {
+                let d = random_in_unit_sphere(&mut sampler).normalize();
+                object.pdf_value(Vec3::zero(), d) * 4.0 * PI
+            }).sum::<f32>() / n as f32;
+            assert!((sum - 1.0).abs() < 0.03, "{}", sum);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{Camera, Color, FrameBuffer, Hitable, Renderer, Vec3};
+
+const MAGIC: &[u8; 8] = b"RTWACC01";
+
+/// bytes before the pixels: magic, width, height, seed and samples
+const HEADER_LEN: u64 = 8 + 4 + 4 + 8 + 4;
+
+/// float accumulation buffer of per-pixel radiance sums for progressive rendering
+pub struct Accumulator {
+    width: u32,
+    height: u32,
+    seed: u64,
+    samples: u32,
+    sum: Vec<Color>,
+}
+
+impl Accumulator {
+    /// construct new empty accumulator for renders of `renderer`
+    pub fn new(renderer: &Renderer) -> Self {
+        Accumulator {
+            width: renderer.width,
+            height: renderer.height,
+            seed: renderer.seed,
+            samples: 0,
+            sum: vec![Vec3::zero(); (renderer.width * renderer.height) as usize],
+        }
+    }
+
+    /// samples per pixel accumulated so far
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// whether this buffer can be continued by `renderer` (same size and seed)
+    pub fn matches(&self, renderer: &Renderer) -> bool {
+        (self.width, self.height, self.seed) == (renderer.width, renderer.height, renderer.seed)
+    }
+
+    /// current image estimate (average of accumulated samples)
+    pub fn frame(&self) -> FrameBuffer {
+        let mut fb = FrameBuffer::new(self.width, self.height);
+        if self.samples > 0 {
+            for (p, s) in fb.pixels_mut().iter_mut().zip(self.sum.iter()) {
+                *p = *s / self.samples as f32;
+            }
+        }
+        fb
+    }
+
+    /// write accumulation buffer to `path`, replacing the file atomically
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+
+        {
+            let mut w = BufWriter::new(File::create(&tmp)?);
+            w.write_all(MAGIC)?;
+            w.write_all(&self.width.to_le_bytes())?;
+            w.write_all(&self.height.to_le_bytes())?;
+            w.write_all(&self.seed.to_le_bytes())?;
+            w.write_all(&self.samples.to_le_bytes())?;
+            for c in &self.sum {
+                for i in 0..3 {
+                    w.write_all(&c[i].to_le_bytes())?;
+                }
+            }
+            w.flush()?;
+        }
+
+        std::fs::rename(tmp, path)
+    }
+
+    /// read accumulation buffer written by `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut r = BufReader::new(file);
+
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an accumulation buffer"));
+        }
+
+        let mut u32_buf = [0u8; 4];
+        let mut read_u32 = |r: &mut BufReader<File>| -> io::Result<u32> {
+            r.read_exact(&mut u32_buf)?;
+            Ok(u32::from_le_bytes(u32_buf))
+        };
+        let width = read_u32(&mut r)?;
+        let height = read_u32(&mut r)?;
+        let mut seed = [0u8; 8];
+        r.read_exact(&mut seed)?;
+        let samples = read_u32(&mut r)?;
+
+        // check the size against the file before allocating for it
+        let pixels = width.checked_mul(height)
+            .filter(|&n| len.checked_sub(HEADER_LEN) == Some(n as u64 * 12))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "accumulation buffer size mismatch"))?;
+
+        let mut sum = Vec::with_capacity(pixels as usize);
+        let mut f = [0u8; 4];
+        for _ in 0..pixels {
+            let mut c = Vec3::zero();
+            for i in 0..3 {
+                r.read_exact(&mut f)?;
+                c[i] = f32::from_le_bytes(f);
+            }
+            sum.push(c);
+        }
+
+        Ok(Accumulator {
+            width,
+            height,
+            seed: u64::from_le_bytes(seed),
+            samples,
+            sum,
+        })
+    }
+}
+
+impl Renderer {
+    /// add `samples` more samples per pixel to `acc`
+    ///
+    /// samples continue the sequence of earlier passes, so accumulating N samples in
+    /// any number of passes gives the same image as `render` with N samples
+    pub fn render_pass(&self, world: &dyn Hitable, camera: &Camera, acc: &mut Accumulator, samples: u32) {
+        assert!(acc.matches(self), "accumulator does not match renderer size and seed");
+
+        let start = acc.samples;
+        let lights = self.lights(world);
+        let sums = self.render_tiles(|x, y| {
+            (start..start + samples)
+                .map(|s| self.sample(world, &lights, camera, x, y, s))
+                .fold(acc.sum[(y * self.width + x) as usize], |a, b| a + b)
+        });
+
+        acc.sum.copy_from_slice(sums.pixels());
+        acc.samples += samples;
+    }
+
+    /// render passes of `pass_samples` samples into `acc` until it holds `self.samples`,
+    /// calling `on_pass` after each pass
+    pub fn render_progressive<F>(&self, world: &dyn Hitable, camera: &Camera, acc: &mut Accumulator,
+                                 pass_samples: u32, mut on_pass: F)
+        where F: FnMut(&Accumulator)
+    {
+        assert!(pass_samples > 0, "pass needs at least one sample");
+
+        while acc.samples < self.samples {
+            let n = pass_samples.min(self.samples - acc.samples);
+            self.render_pass(world, camera, acc, n);
+            on_pass(acc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{HitableList, Lambertian, Metal, Sphere};
+
+    fn scene() -> (HitableList<'static>, Camera, Renderer) {
+        let mut world = HitableList::default();
+        world.push(Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5,
+            Box::new(Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
+        world.push(Sphere::new(Vec3::new(0.0, -100.5, -1.0), 100.0,
+            Box::new(Metal::new(Vec3::new(0.8, 0.8, 0.8), 0.5))));
+
+        let mut renderer = Renderer::new(6, 4);
+        renderer.samples = 10;
+        let cam = Camera::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0),
+            90.0, renderer.aspect(), 0.0, 1.0);
+
+        (world, cam, renderer)
+    }
+
+    #[test]
+    fn test_progressive_matches_render() {
+        let (world, cam, renderer) = scene();
+        let mut acc = Accumulator::new(&renderer);
+
+        let mut passes = Vec::new();
+        renderer.render_progressive(&world, &cam, &mut acc, 3, |acc| passes.push(acc.samples()));
+
+        assert_eq!(passes, vec![3, 6, 9, 10]);
+        assert_eq!(acc.frame().pixels(), renderer.render(&world, &cam).pixels());
+    }
+
+    #[test]
+    fn test_save_resume() {
+        let (world, cam, renderer) = scene();
+        let path = std::env::temp_dir().join("rtw_test_accumulator.bin");
+
+        let mut acc = Accumulator::new(&renderer);
+        renderer.render_pass(&world, &cam, &mut acc, 4);
+        acc.save(&path).unwrap();
+
+        let mut resumed = Accumulator::load(&path).unwrap();
+        assert!(resumed.matches(&renderer));
+        assert_eq!(resumed.samples(), 4);
+        assert_eq!(resumed.frame().pixels(), acc.frame().pixels());
+
+        renderer.render_progressive(&world, &cam, &mut resumed, 4, |_| {});
+        assert_eq!(resumed.frame().pixels(), renderer.render(&world, &cam).pixels());
+    }
+
+    #[test]
+    fn test_load_corrupt() {
+        let (_, _, renderer) = scene();
+        let path = std::env::temp_dir().join("rtw_test_accumulator_corrupt.bin");
+        Accumulator::new(&renderer).save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        // overflowing, oversized and truncated buffers
+        let mut huge = bytes.clone();
+        huge[8..16].copy_from_slice(&[0xff; 8]);
+        let mut wide = bytes.clone();
+        wide[8..12].copy_from_slice(&1_000_000u32.to_le_bytes());
+        for corrupt in [huge, wide, bytes[..bytes.len() - 1].to_vec()] {
+            std::fs::write(&path, corrupt).unwrap();
+            let err = Accumulator::load(&path).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,609 @@
+use std::f32::consts::PI;
+
+use crate::{Aabb, HitRecord, Hitable, Material, Ray, Solid, Span, Vec3};
+use crate::csg::boundary_spans;
+
+/// sweep and caps shared by the quadrics, which stand on the xz plane around the +y axis of their local frame
+///
+/// phi goes from +x towards +z, u follows phi over the sweep and v goes up the axis
+struct Surface {
+    /// origin of local frame
+    base: Vec3,
+    /// swept angle in radians
+    phi_max: f32,
+    capped: bool,
+    material: Box<dyn Material>,
+}
+
+impl Surface {
+    fn new(base: Vec3, material: Box<dyn Material>) -> Self {
+        Surface {
+            base,
+            phi_max: 2.0 * PI,
+            capped: true,
+            material,
+        }
+    }
+
+    /// whether the sweep is a full revolution
+    fn swept(&self) -> bool {
+        self.phi_max >= 2.0 * PI
+    }
+
+    /// `ray` origin and direction relative to the local frame
+    fn local(&self, ray: &Ray) -> (Vec3, Vec3) {
+        (ray.origin - self.base, ray.direction)
+    }
+
+    /// angle of local point `p` around the axis in [0, 2pi), `None` outside the sweep
+    fn phi(&self, p: Vec3) -> Option<f32> {
+        let phi = p.z.atan2(p.x);
+        let phi = if phi < 0.0 { phi + 2.0 * PI } else { phi };
+        if phi <= self.phi_max { Some(phi) } else { None }
+    }
+
+    fn record(&self, ray: &Ray, t: f32, normal: Vec3, u: f32, v: f32) -> HitRecord<'_> {
+        HitRecord {
+            t,
+            point: ray.point_at(t),
+            normal,
+            u,
+            v,
+            material: self.material.as_ref(),
+        }
+    }
+
+    /// closest root of `a t^2 + b t + c` in (`t_min`, `t_max`) whose local point passes `accept`
+    fn side<'a>(&'a self, ray: &Ray, (a, b, c): (f32, f32, f32), t_min: f32, t_max: f32,
+                accept: impl Fn(Vec3, f32) -> Option<HitRecord<'a>>) -> Option<HitRecord<'a>> {
+        let (o, d) = self.local(ray);
+        let (t0, t1) = solve_quadratic(a, b, c)?;
+        [t0, t1].iter()
+            .filter(|&&t| t > t_min && t < t_max)
+            .find_map(|&t| accept(o + t * d, t))
+    }
+
+    /// disk cap of `radius` at height `y`, facing +y if `up`
+    fn cap(&self, ray: &Ray, y: f32, radius: f32, up: bool, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let (o, d) = self.local(ray);
+        if !self.capped || d.y == 0.0 {
+            return None;
+        }
+
+        let t = (y - o.y) / d.y;
+        let p = o + t * d;
+        let r_squared = p.x * p.x + p.z * p.z;
+        if t <= t_min || t >= t_max || r_squared > radius * radius {
+            return None;
+        }
+
+        let phi = self.phi(p)?;
+        let normal = Vec3::new(0.0, if up { 1.0 } else { -1.0 }, 0.0);
+        Some(self.record(ray, t, normal, phi / self.phi_max, r_squared.sqrt() / radius))
+    }
+
+    /// box of a solid of revolution with `radius` between heights `y0` and `y1`
+    fn bounding_box(&self, radius: f32, y0: f32, y1: f32) -> Aabb {
+        Aabb::new(self.base + Vec3::new(-radius, y0, -radius), self.base + Vec3::new(radius, y1, radius))
+    }
+}
+
+/// closest of two hits
+fn closest<'a>(a: Option<HitRecord<'a>>, b: Option<HitRecord<'a>>) -> Option<HitRecord<'a>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a.t <= b.t { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+macro_rules! sweep_builders {
+    ($name:ident) => {
+        impl $name {
+            /// sweep only `degrees` around the axis instead of a full revolution
+            pub fn with_phi_max(mut self, degrees: f32) -> Self {
+                self.surface.phi_max = degrees.clamp(0.0, 360.0).to_radians();
+                self
+            }
+
+            /// remove the caps
+            pub fn open(mut self) -> Self {
+                self.surface.capped = false;
+                self
+            }
+        }
+    };
+}
+
+/// capped cylinder standing on `base`
+pub struct Cylinder {
+    surface: Surface,
+    radius: f32,
+    height: f32,
+}
+
+impl Cylinder {
+    /// construct new cylinder around the vertical axis through `base`
+    pub fn new(base: Vec3, radius: f32, height: f32, material: Box<dyn Material>) -> Self {
+        Cylinder { surface: Surface::new(base, material), radius, height }
+    }
+}
+
+sweep_builders!(Cylinder);
+
+impl Hitable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let s = &self.surface;
+        let (o, d) = s.local(ray);
+        let coeff = (d.x * d.x + d.z * d.z,
+                     2.0 * (o.x * d.x + o.z * d.z),
+                     o.x * o.x + o.z * o.z - self.radius * self.radius);
+
+        let side = s.side(ray, coeff, t_min, t_max, |p, t| {
+            if p.y < 0.0 || p.y > self.height {
+                return None;
+            }
+            let phi = s.phi(p)?;
+            let normal = Vec3::new(p.x, 0.0, p.z) / self.radius;
+            Some(s.record(ray, t, normal, phi / s.phi_max, p.y / self.height))
+        });
+        let t_max = side.as_ref().map_or(t_max, |rec| rec.t);
+
+        let caps = closest(s.cap(ray, 0.0, self.radius, false, t_min, t_max),
+                           s.cap(ray, self.height, self.radius, true, t_min, t_max));
+        closest(side, caps)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.surface.bounding_box(self.radius, 0.0, self.height))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.surface.material.as_ref());
+    }
+}
+
+/// solid while capped and fully swept, an open or partial surface has no spans
+impl Solid for Cylinder {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        match self.surface.capped && self.surface.swept() {
+            true => boundary_spans(self, ray),
+            false => Vec::new(),
+        }
+    }
+}
+
+/// cone with base disk on `base` and apex above it
+pub struct Cone {
+    surface: Surface,
+    radius: f32,
+    height: f32,
+}
+
+impl Cone {
+    /// construct new cone with base `radius` and apex at `height` on the vertical axis through `base`
+    pub fn new(base: Vec3, radius: f32, height: f32, material: Box<dyn Material>) -> Self {
+        Cone { surface: Surface::new(base, material), radius, height }
+    }
+}
+
+sweep_builders!(Cone);
+
+impl Hitable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        // x^2 + z^2 = k^2 (h - y)^2
+        let s = &self.surface;
+        let (o, d) = s.local(ray);
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let oy = self.height - o.y;
+        let coeff = (d.x * d.x + d.z * d.z - k2 * d.y * d.y,
+                     2.0 * (o.x * d.x + o.z * d.z + k2 * oy * d.y),
+                     o.x * o.x + o.z * o.z - k2 * oy * oy);
+
+        let side = s.side(ray, coeff, t_min, t_max, |p, t| {
+            if p.y < 0.0 || p.y > self.height {
+                return None;
+            }
+            let phi = s.phi(p)?;
+            let normal = Vec3::new(p.x, k2 * (self.height - p.y), p.z).normalize();
+            Some(s.record(ray, t, normal, phi / s.phi_max, p.y / self.height))
+        });
+        let t_max = side.as_ref().map_or(t_max, |rec| rec.t);
+
+        closest(side, s.cap(ray, 0.0, self.radius, false, t_min, t_max))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.surface.bounding_box(self.radius, 0.0, self.height))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.surface.material.as_ref());
+    }
+}
+
+/// solid while capped and fully swept, an open or partial surface has no spans
+impl Solid for Cone {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        match self.surface.capped && self.surface.swept() {
+            true => boundary_spans(self, ray),
+            false => Vec::new(),
+        }
+    }
+}
+
+/// paraboloid bowl with its vertex on `base`, opening upwards
+pub struct Paraboloid {
+    surface: Surface,
+    radius: f32,
+    height: f32,
+}
+
+impl Paraboloid {
+    /// construct new paraboloid of `radius` at the rim, `height` above the vertex
+    pub fn new(base: Vec3, radius: f32, height: f32, material: Box<dyn Material>) -> Self {
+        Paraboloid { surface: Surface::new(base, material), radius, height }
+    }
+}
+
+sweep_builders!(Paraboloid);
+
+impl Hitable for Paraboloid {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        // h (x^2 + z^2) = r^2 y
+        let s = &self.surface;
+        let (o, d) = s.local(ray);
+        let (h, r2) = (self.height, self.radius * self.radius);
+        let coeff = (h * (d.x * d.x + d.z * d.z),
+                     2.0 * h * (o.x * d.x + o.z * d.z) - r2 * d.y,
+                     h * (o.x * o.x + o.z * o.z) - r2 * o.y);
+
+        let side = s.side(ray, coeff, t_min, t_max, |p, t| {
+            if p.y < 0.0 || p.y > self.height {
+                return None;
+            }
+            let phi = s.phi(p)?;
+            let normal = Vec3::new(2.0 * h * p.x, -r2, 2.0 * h * p.z).normalize();
+            Some(s.record(ray, t, normal, phi / s.phi_max, p.y / self.height))
+        });
+        let t_max = side.as_ref().map_or(t_max, |rec| rec.t);
+
+        closest(side, s.cap(ray, self.height, self.radius, true, t_min, t_max))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.surface.bounding_box(self.radius, 0.0, self.height))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.surface.material.as_ref());
+    }
+}
+
+/// solid while capped and fully swept, an open or partial surface has no spans
+impl Solid for Paraboloid {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        match self.surface.capped && self.surface.swept() {
+            true => boundary_spans(self, ray),
+            false => Vec::new(),
+        }
+    }
+}
+
+/// torus around the vertical axis through `center`
+pub struct Torus {
+    surface: Surface,
+    major_radius: f32,
+    minor_radius: f32,
+}
+
+impl Torus {
+    /// construct new torus, `major_radius` from the axis to the tube center and tube `minor_radius`
+    ///
+    /// v goes around the tube from its inner side, under it and reaches 0.5 on the outer equator
+    pub fn new(center: Vec3, major_radius: f32, minor_radius: f32, material: Box<dyn Material>) -> Self {
+        let mut surface = Surface::new(center, material);
+        surface.capped = false;
+        Torus { surface, major_radius, minor_radius }
+    }
+
+    /// sweep only `degrees` around the axis instead of a full revolution, the tube ends stay open
+    pub fn with_phi_max(mut self, degrees: f32) -> Self {
+        self.surface.phi_max = degrees.clamp(0.0, 360.0).to_radians();
+        self
+    }
+}
+
+impl Hitable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let s = &self.surface;
+        let (o, d) = s.local(ray);
+
+        // solve from the point closest to the center to keep the coefficients small
+        let dd = f64::from(d.dot(d));
+        let shift = -f64::from(o.dot(d)) / dd;
+        let o = o + shift as f32 * d;
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + z^2)
+        let (o64, d64) = ([o.x, o.y, o.z].map(f64::from), [d.x, d.y, d.z].map(f64::from));
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        let r2 = f64::from(self.major_radius).powi(2);
+        let od = dot(o64, d64);
+        let k = dot(o64, o64) + r2 - f64::from(self.minor_radius).powi(2);
+        let a = d64[0] * d64[0] + d64[2] * d64[2];
+        let b = 2.0 * (o64[0] * d64[0] + o64[2] * d64[2]);
+        let c = o64[0] * o64[0] + o64[2] * o64[2];
+
+        let (roots, n) = solve_quartic([
+            k * k - 4.0 * r2 * c,
+            4.0 * od * k - 4.0 * r2 * b,
+            4.0 * od * od + 2.0 * dd * k - 4.0 * r2 * a,
+            4.0 * dd * od,
+            dd * dd,
+        ]);
+
+        let mut roots = roots[..n].iter()
+            .map(|&t| (t + shift) as f32)
+            .filter(|&t| t > t_min && t < t_max)
+            .collect::<Vec<_>>();
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        roots.into_iter().find_map(|t| {
+            let p = ray.origin - s.base + t * ray.direction;
+            let phi = s.phi(p)?;
+            let axial = (p.x * p.x + p.z * p.z).sqrt();
+            let ring = Vec3::new(p.x, 0.0, p.z) * (self.major_radius / axial);
+            let normal = (p - ring).normalize();
+            let theta = p.y.atan2(axial - self.major_radius);
+            Some(s.record(ray, t, normal, phi / s.phi_max, (theta + PI) / (2.0 * PI)))
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (major, minor) = (self.major_radius.abs(), self.minor_radius.abs());
+        Some(self.surface.bounding_box(major + minor, -minor, minor))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.surface.material.as_ref());
+    }
+}
+
+/// solid while fully swept, a partial ring has no spans
+impl Solid for Torus {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        match self.surface.swept() {
+            true => boundary_spans(self, ray),
+            false => Vec::new(),
+        }
+    }
+}
+
+/// real roots of `a x^2 + b x + c` in ascending order, also solving the linear case
+fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a == 0.0 {
+        return match b == 0.0 {
+            true => None,
+            false => Some((-c / b, -c / b)),
+        };
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // avoid cancellation, see: Numerical Recipes 5.6
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (t0, t1) = match q == 0.0 {
+        true => (0.0, 0.0),
+        false => (q / a, c / q),
+    };
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+/// largest real root of monic cubic `x^3 + a x^2 + b x + c`
+fn cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+
+    if r * r < q * q * q {
+        let theta = (r / (q * q * q).sqrt()).acos();
+        -2.0 * q.sqrt() * ((theta + 2.0 * std::f64::consts::PI) / 3.0).cos() - a / 3.0
+    } else {
+        let s = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let t = if s == 0.0 { 0.0 } else { q / s };
+        s + t - a / 3.0
+    }
+}
+
+/// real roots of quartic with coefficients `c[i]` of `x^i` (Ferrari's method, polished by newton iterations)
+fn solve_quartic(c: [f64; 5]) -> ([f64; 4], usize) {
+    let mut roots = [0.0; 4];
+    let mut n = 0;
+    let (a, b, cc, d) = (c[3] / c[4], c[2] / c[4], c[1] / c[4], c[0] / c[4]);
+
+    // depressed quartic y^4 + p y^2 + q y + r with x = y - a / 4
+    let p = b - 3.0 * a * a / 8.0;
+    let q = cc - a * b / 2.0 + a * a * a / 8.0;
+    let r = d - a * cc / 4.0 + a * a * b / 16.0 - 3.0 * a * a * a * a / 256.0;
+
+    let mut push_quadratic = |b: f64, c: f64| {
+        let discriminant = b * b - 4.0 * c;
+        if discriminant >= 0.0 {
+            let sqrt = discriminant.sqrt();
+            for y in [(-b - sqrt) / 2.0, (-b + sqrt) / 2.0] {
+                roots[n] = y - a / 4.0;
+                n += 1;
+            }
+        }
+    };
+
+    // m solves the resolvent cubic, then the quartic splits into two quadratics
+    let m = cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+    if m <= 1e-12 {
+        // biquadratic y^4 + p y^2 + r
+        let discriminant = p * p - 4.0 * r;
+        if discriminant >= 0.0 {
+            for z in [(-p - discriminant.sqrt()) / 2.0, (-p + discriminant.sqrt()) / 2.0] {
+                if z >= 0.0 {
+                    push_quadratic(0.0, -z);
+                }
+            }
+        }
+    } else {
+        let s = (2.0 * m).sqrt();
+        push_quadratic(s, p / 2.0 + m - q / (2.0 * s));
+        push_quadratic(-s, p / 2.0 + m + q / (2.0 * s));
+    }
+
+    for x in &mut roots[..n] {
+        for _ in 0..2 {
+            let f = (((c[4] * *x + c[3]) * *x + c[2]) * *x + c[1]) * *x + c[0];
+            let df = ((4.0 * c[4] * *x + 3.0 * c[3]) * *x + 2.0 * c[2]) * *x + c[1];
+            if df != 0.0 {
+                *x -= f / df;
+            }
+        }
+    }
+
+    (roots, n)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Lambertian;
+
+    fn material() -> Box<dyn Material> {
+        Box::new(Lambertian::new(Vec3::unit()))
+    }
+
+    fn ray(origin: Vec3, direction: Vec3) -> Ray {
+        Ray::new(origin, direction)
+    }
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).norm() < 1e-4
+    }
+
+    #[test]
+    fn test_solvers() {
+        assert_eq!(solve_quadratic(1.0, -3.0, 2.0), Some((1.0, 2.0)));
+        assert_eq!(solve_quadratic(0.0, 2.0, -4.0), Some((2.0, 2.0)));
+        assert_eq!(solve_quadratic(1.0, 0.0, 1.0), None);
+
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let (roots, n) = solve_quartic([24.0, -50.0, 35.0, -10.0, 1.0]);
+        let mut roots = roots[..n].to_vec();
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(n, 4);
+        for (r, e) in roots.iter().zip(&[1.0, 2.0, 3.0, 4.0]) {
+            assert!((r - e).abs() < 1e-9, "{:?}", roots);
+        }
+
+        // (x^2 + 1)(x - 2)(x + 5), two real roots
+        let (roots, n) = solve_quartic([-10.0, 3.0, -9.0, 3.0, 1.0]);
+        assert_eq!(n, 2);
+        assert!(roots[..n].iter().all(|&r| (r - 2.0).abs() < 1e-9 || (r + 5.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_cylinder() {
+        let cylinder = Cylinder::new(Vec3::new(0.0, -1.0, 0.0), 1.0, 2.0, material());
+
+        let rec = cylinder.hit(&ray(Vec3::new(-5.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert!(close(rec.normal, Vec3::new(-1.0, 0.0, 0.0)));
+        assert!((rec.u - 0.5).abs() < 1e-6 && (rec.v - 0.75).abs() < 1e-6);
+
+        let rec = cylinder.hit(&ray(Vec3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (4.0, Vec3::new(0.0, 1.0, 0.0)));
+        assert!((rec.v - 0.5).abs() < 1e-6);
+
+        // an open tube is hit on the inside of the far wall
+        let tube = Cylinder::new(Vec3::new(0.0, -1.0, 0.0), 1.0, 2.0, material()).open();
+        let rec = tube.hit(&ray(Vec3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX);
+        assert!(rec.is_none());
+        let rec = tube.hit(&ray(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)), 0.001, f32::MAX).unwrap();
+        assert!(close(rec.point, Vec3::new(0.0, 0.0, 1.0)));
+
+        // half cylinder on the +z side
+        let half = Cylinder::new(Vec3::new(0.0, -1.0, 0.0), 1.0, 2.0, material()).with_phi_max(180.0);
+        let rec = half.hit(&ray(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0)), 0.001, f32::MAX).unwrap();
+        assert!(close(rec.point, Vec3::new(0.0, 0.0, 1.0)));
+        assert!((rec.u - 0.5).abs() < 1e-6);
+        assert!(half.hit(&ray(Vec3::new(0.0, -5.0, -0.5), Vec3::new(0.0, 1.0, 0.0)), 0.001, f32::MAX).is_none());
+
+        let bbox = cylinder.bounding_box().unwrap();
+        assert_eq!((bbox.min, bbox.max), (Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_cone() {
+        let cone = Cone::new(Vec3::zero(), 1.0, 2.0, material());
+
+        // side at height 1 has radius 0.5, normal tilted up by the slope
+        let rec = cone.hit(&ray(Vec3::new(5.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 4.5).abs() < 1e-5);
+        assert!(close(rec.normal, Vec3::new(2.0, 1.0, 0.0).normalize()));
+        assert!((rec.v - 0.5).abs() < 1e-6);
+
+        let rec = cone.hit(&ray(Vec3::new(0.25, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert_eq!((rec.t, rec.normal), (3.0, Vec3::new(0.0, -1.0, 0.0)));
+
+        // the mirrored nappe above the apex is rejected
+        let cone = cone.open();
+        let rec = cone.hit(&ray(Vec3::new(0.25, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!(close(rec.point, Vec3::new(0.25, 1.5, 0.0)));
+    }
+
+    #[test]
+    fn test_paraboloid() {
+        let bowl = Paraboloid::new(Vec3::zero(), 2.0, 4.0, material());
+
+        // y = x^2
+        let rec = bowl.hit(&ray(Vec3::new(1.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 6.0).abs() < 1e-5, "{}", rec.t);
+        assert_eq!(rec.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        let bowl = bowl.open();
+        let rec = bowl.hit(&ray(Vec3::new(1.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 9.0).abs() < 1e-5, "{}", rec.t);
+        assert!(close(rec.normal, Vec3::new(2.0, -1.0, 0.0).normalize()));
+        assert!((rec.v - 0.25).abs() < 1e-6);
+
+        let rec = bowl.hit(&ray(Vec3::new(-5.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-5);
+        assert!(bowl.hit(&ray(Vec3::new(-5.0, 5.0, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.001, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_torus() {
+        let torus = Torus::new(Vec3::new(0.0, 0.0, -10.0), 2.0, 0.5, material());
+
+        let rec = torus.hit(&ray(Vec3::new(-5.0, 0.0, -10.0), Vec3::new(1.0, 0.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 2.5).abs() < 1e-4, "{}", rec.t);
+        assert!(close(rec.normal, Vec3::new(-1.0, 0.0, 0.0)));
+        assert!((rec.v - 0.5).abs() < 1e-6);
+
+        // from the center the inner wall is hit facing the axis
+        let rec = torus.hit(&ray(Vec3::new(0.0, 0.0, -10.0), Vec3::new(0.0, 0.0, 3.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 0.5).abs() < 1e-4, "{}", rec.t);
+        assert!(close(rec.normal, Vec3::new(0.0, 0.0, -1.0)));
+
+        // down onto the tube top, and through the hole
+        let rec = torus.hit(&ray(Vec3::new(2.0, 100.0, -10.0), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 99.5).abs() < 1e-3, "{}", rec.t);
+        assert!(close(rec.normal, Vec3::new(0.0, 1.0, 0.0)));
+        assert!(torus.hit(&ray(Vec3::new(0.0, 100.0, -10.0), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).is_none());
+
+        // quarter torus only covers +x to +z
+        let quarter = Torus::new(Vec3::zero(), 2.0, 0.5, material()).with_phi_max(90.0);
+        assert!(quarter.hit(&ray(Vec3::new(2.0, 5.0, 0.1), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).is_some());
+        assert!(quarter.hit(&ray(Vec3::new(2.0, 5.0, -0.1), Vec3::new(0.0, -1.0, 0.0)), 0.001, f32::MAX).is_none());
+
+        let bbox = torus.bounding_box().unwrap();
+        assert_eq!((bbox.min, bbox.max), (Vec3::new(-2.5, -0.5, -12.5), Vec3::new(2.5, 0.5, -7.5)));
+    }
+}
@@ -1,18 +1,26 @@
 use crate::Vec3;
 
-/// ray with origin and direction
+/// ray with origin, direction and time
 #[derive(PartialEq)]
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
+    /// point in time the ray exists at, for moving objects
+    pub time: f32,
 }
 
 impl Ray {
-    /// construct new ray
+    /// construct new ray at time 0
     pub fn new(a: Vec3, b: Vec3) -> Self {
+        Self::with_time(a, b, 0.0)
+    }
+
+    /// construct new ray at `time`
+    pub fn with_time(origin: Vec3, direction: Vec3, time: f32) -> Self {
         Ray {
-            origin: a,
-            direction: b,
+            origin,
+            direction,
+            time,
         }
     }
 
@@ -0,0 +1,535 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use rayon::prelude::*;
+
+use crate::{Camera, Color, Hitable, LightList, Ray, Sampler, Vec3};
+use crate::power_heuristic;
+use crate::{Environment, GradientEnvironment};
+use crate::ToneMapping;
+
+/// float framebuffer holding linear radiance, row major from the top row
+pub struct FrameBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl FrameBuffer {
+    /// construct new black framebuffer
+    pub fn new(width: u32, height: u32) -> Self {
+        FrameBuffer {
+            width,
+            height,
+            pixels: vec![Vec3::zero(); (width * height) as usize],
+        }
+    }
+
+    /// framebuffer width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// framebuffer height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// pixel colors, row major from the top row
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// mutable pixel colors, row major from the top row
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    /// get pixel color at (`x`, `y`), `y` counts from the top row
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// set pixel color at (`x`, `y`), `y` counts from the top row
+    pub fn set(&mut self, x: u32, y: u32, c: Color) {
+        self.pixels[(y * self.width + x) as usize] = c;
+    }
+
+    /// convert to 8-bit RGB image with default tone mapping (same as `vec_to_rgb`)
+    pub fn to_image(&self) -> image::RgbImage {
+        self.to_image_with(&ToneMapping::default())
+    }
+
+    /// convert to 8-bit RGB image with `tone_mapping`
+    pub fn to_image_with(&self, tone_mapping: &ToneMapping) -> image::RgbImage {
+        image::ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            image::Rgb(tone_mapping.to_rgb8(self.get(x, y), x, y))
+        })
+    }
+
+    /// save image with default tone mapping, format is deduced from path extension
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.save_with(path, &ToneMapping::default())
+    }
+
+    /// save image, format is deduced from path extension
+    ///
+    /// `.exr`, `.hdr` and `.pfm` keep linear float radiance, other formats are tone mapped
+    /// with `tone_mapping` to 8-bit images
+    pub fn save_with<P: AsRef<Path>>(&self, path: P, tone_mapping: &ToneMapping) -> io::Result<()> {
+        let path = path.as_ref();
+        let ext = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match ext.as_deref() {
+            Some("exr") => self.save_exr(path),
+            Some("hdr") => self.save_hdr(path),
+            Some("pfm") => self.save_pfm(path),
+            _ => self.to_image_with(tone_mapping).save(path),
+        }
+    }
+
+    /// save linear radiance as OpenEXR with 32-bit float RGB channels
+    pub fn save_exr<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        exr::prelude::write_rgb_file(path, self.width as usize, self.height as usize, |x, y| {
+            let c = self.pixels[y * self.width as usize + x];
+            (c.x, c.y, c.z)
+        }).map_err(invalid_data)
+    }
+
+    /// save linear radiance as Radiance RGBE `.hdr`
+    pub fn save_hdr<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let pixels = self.pixels.iter()
+            .map(|c| image::Rgb([c.x.max(0.0), c.y.max(0.0), c.z.max(0.0)]))
+            .collect::<Vec<_>>();
+        let file = io::BufWriter::new(std::fs::File::create(path)?);
+        image::hdr::HDREncoder::new(file).encode(&pixels, self.width as usize, self.height as usize)
+    }
+
+    /// save linear radiance as little endian color PFM (portable float map)
+    pub fn save_pfm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        write!(file, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+
+        // pfm rows go from the bottom up
+        for row in self.pixels.chunks(self.width.max(1) as usize).rev() {
+            for c in row {
+                for v in &[c.x, c.y, c.z] {
+                    file.write_all(&v.to_le_bytes())?;
+                }
+            }
+        }
+
+        file.flush()
+    }
+}
+
+fn invalid_data<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// order in which image tiles are scheduled
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileOrder {
+    /// row by row from the top left
+    Scanline,
+    /// outwards from the image center
+    Spiral,
+}
+
+/// rectangular image region rendered as a unit of work
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tile {
+    /// left pixel column
+    pub x: u32,
+    /// top pixel row
+    pub y: u32,
+    /// tile width in pixels
+    pub width: u32,
+    /// tile height in pixels
+    pub height: u32,
+}
+
+/// path tracing renderer
+pub struct Renderer {
+    /// image width in pixels
+    pub width: u32,
+    /// image height in pixels
+    pub height: u32,
+    /// samples per pixel
+    pub samples: u32,
+    /// max scatter depth of a path
+    pub max_depth: u32,
+    /// ray epsilon to avoid self intersection (shadow acne)
+    pub t_min: f32,
+    /// radiance of rays leaving the scene
+    pub environment: Box<dyn Environment>,
+    /// global random seed, same seed gives identical renders
+    pub seed: u64,
+    /// edge length of square render tiles in pixels
+    pub tile_size: u32,
+    /// tile scheduling order
+    pub tile_order: TileOrder,
+    /// conversion of rendered radiance to 8-bit output images
+    pub tone_mapping: ToneMapping,
+    /// sample emissive objects directly (next event estimation) combined with multiple importance sampling
+    pub light_sampling: bool,
+}
+
+impl Renderer {
+    /// construct new renderer with 100 samples, max depth of 50, `t_min` of 0.001,
+    /// 16x16 tiles in spiral order and light sampling
+    pub fn new(width: u32, height: u32) -> Self {
+        Renderer {
+            width,
+            height,
+            samples: 100,
+            max_depth: 50,
+            t_min: 0.001,
+            environment: Box::new(GradientEnvironment::default()),
+            seed: 0,
+            tile_size: 16,
+            tile_order: TileOrder::Spiral,
+            tone_mapping: ToneMapping::default(),
+            light_sampling: true,
+        }
+    }
+
+    /// image aspect ratio (width / height)
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    /// image tiles in scheduling order
+    pub fn tiles(&self) -> Vec<Tile> {
+        let size = self.tile_size.max(1);
+        let (cols, rows) = (self.width.div_ceil(size), self.height.div_ceil(size));
+
+        let mut tiles = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| Tile {
+                x: col * size,
+                y: row * size,
+                width: size.min(self.width - col * size),
+                height: size.min(self.height - row * size),
+            })
+            .collect::<Vec<_>>();
+
+        if self.tile_order == TileOrder::Spiral {
+            // sort by square ring around the center, then by angle within the ring
+            let (cx, cy) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+            let key = |t: &Tile| {
+                let dx = (t.x as f32 + t.width as f32 / 2.0 - cx) / size as f32;
+                let dy = (t.y as f32 + t.height as f32 / 2.0 - cy) / size as f32;
+                (dx.abs().max(dy.abs()).round(), dy.atan2(dx))
+            };
+            tiles.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap());
+        }
+
+        tiles
+    }
+
+    /// evaluate `f(x, y)` for every pixel, rendering tiles in parallel
+    pub fn render_tiles<F>(&self, f: F) -> FrameBuffer
+        where F: Fn(u32, u32) -> Color + Sync
+    {
+        let tiles = self.tiles();
+        let results = tiles.par_iter()
+            .map(|t| {
+                (t.y..t.y + t.height)
+                    .flat_map(|y| (t.x..t.x + t.width).map(move |x| (x, y)))
+                    .map(|(x, y)| f(x, y))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut fb = FrameBuffer::new(self.width, self.height);
+        for (t, pixels) in tiles.iter().zip(results) {
+            for (i, c) in pixels.into_iter().enumerate() {
+                let i = i as u32;
+                fb.set(t.x + i % t.width, t.y + i / t.width, c);
+            }
+        }
+
+        fb
+    }
+
+    /// sampler and jittered camera ray of sample `s` of pixel (`x`, `y`), `y` counts from the top row
+    pub fn camera_ray(&self, camera: &Camera, x: u32, y: u32, s: u32) -> (Ray, Sampler) {
+        let (nx, ny) = (self.width, self.height);
+        let mut sampler = Sampler::new(self.seed, u64::from(y * nx + x), s);
+
+        let u = (x as f32 + sampler.next_f32()) / nx as f32;
+        let v = ((ny - y - 1) as f32 + sampler.next_f32()) / ny as f32;
+        let ray = camera.get_ray(u, v, &mut sampler);
+
+        (ray, sampler)
+    }
+
+    /// lights of `world` sampled for direct lighting, empty if `light_sampling` is off
+    pub fn lights<'a>(&self, world: &'a dyn Hitable) -> LightList<'a> {
+        match self.light_sampling {
+            true => LightList::new(world),
+            false => LightList::empty(),
+        }
+    }
+
+    /// radiance of sample `s` of pixel (`x`, `y`), `y` counts from the top row
+    pub fn sample(&self, world: &dyn Hitable, lights: &LightList, camera: &Camera, x: u32, y: u32, s: u32) -> Color {
+        let (ray, mut sampler) = self.camera_ray(camera, x, y, s);
+        self.color(&ray, world, lights, 0, &mut sampler)
+    }
+
+    /// average radiance of all samples of pixel (`x`, `y`), `y` counts from the top row
+    pub fn render_pixel(&self, world: &dyn Hitable, lights: &LightList, camera: &Camera, x: u32, y: u32) -> Color {
+        (0..self.samples)
+            .map(|s| self.sample(world, lights, camera, x, y, s))
+            .sum::<Color>() / self.samples as f32
+    }
+
+    /// render `world` viewed by `camera` to a float framebuffer
+    pub fn render(&self, world: &dyn Hitable, camera: &Camera) -> FrameBuffer {
+        let lights = self.lights(world);
+        self.render_tiles(|x, y| self.render_pixel(world, &lights, camera, x, y))
+    }
+
+    /// radiance along `ray`, scattering recursively until `max_depth`
+    pub fn color(&self, ray: &Ray, world: &dyn Hitable, lights: &LightList, depth: u32, sampler: &mut Sampler) -> Color {
+        self.trace(ray, world, lights, depth, None, sampler)
+    }
+
+    /// radiance along `ray`, `bsdf_pdf` is the density of the non-specular BSDF sample
+    /// which produced `ray` for weighting emission hit against light sampling
+    fn trace(&self, ray: &Ray, world: &dyn Hitable, lights: &LightList, depth: u32,
+             bsdf_pdf: Option<f32>, sampler: &mut Sampler) -> Color {
+        let rec = match world.hit(ray, self.t_min, f32::MAX) {
+            Some(rec) => rec,
+            None => return self.environment.color(ray.direction),
+        };
+
+        let mut emitted = rec.material.emitted(ray, &rec);
+        if let Some(pdf) = bsdf_pdf {
+            if !lights.is_empty() && rec.material.is_emissive() {
+                emitted *= power_heuristic(pdf, lights.pdf_value(ray.origin, ray.direction));
+            }
+        }
+
+        if depth >= self.max_depth {
+            return emitted;
+        }
+        let bsdf = match rec.material.sample(ray, &rec, sampler) {
+            Some(bsdf) => bsdf,
+            None => return emitted,
+        };
+        let scattered = Ray::with_time(rec.point, bsdf.direction, ray.time);
+
+        if bsdf.specular || lights.is_empty() {
+            return emitted + bsdf.weight *
+                self.trace(&scattered, world, lights, depth + 1, None, sampler);
+        }
+
+        // next event estimation, weighted against finding the light by scattering
+        let mut direct = Vec3::zero();
+        let direction = lights.random_direction(rec.point, sampler);
+        let light_pdf = lights.pdf_value(rec.point, direction);
+        if light_pdf > 0.0 {
+            let f = rec.material.eval(ray, &rec, direction);
+            if f != Vec3::zero() {
+                let shadow = Ray::with_time(rec.point, direction, ray.time);
+                if let Some(lrec) = world.hit(&shadow, self.t_min, f32::MAX) {
+                    if lrec.material.is_emissive() {
+                        let weight = power_heuristic(light_pdf, rec.material.pdf(ray, &rec, direction));
+                        direct = f * lrec.material.emitted(&shadow, &lrec) * (weight / light_pdf);
+                    }
+                }
+            }
+        }
+
+        emitted + direct + bsdf.weight *
+            self.trace(&scattered, world, lights, depth + 1, Some(bsdf.pdf), sampler)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::HitableList;
+
+    #[test]
+    fn test_sky() {
+        let renderer = Renderer::new(4, 2);
+        let world = HitableList::default();
+
+        let up = Ray::new(Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(renderer.color(&up, &world, &LightList::empty(), 0, &mut Sampler::from_seed(0)), Vec3::new(0.5, 0.7, 1.0));
+
+        let down = Ray::new(Vec3::zero(), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(renderer.color(&down, &world, &LightList::empty(), 0, &mut Sampler::from_seed(0)), Vec3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_emission() {
+        let mut renderer = Renderer::new(4, 2);
+        renderer.environment = Box::new(crate::ConstantEnvironment::new(Vec3::zero()));
+
+        let mut world = HitableList::default();
+        world.push(crate::Sphere::new(Vec3::new(0.0, 0.0, -2.0), 1.0,
+            Box::new(crate::DiffuseLight::new(Vec3::new(4.0, 4.0, 4.0)))));
+
+        let hit = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(renderer.color(&hit, &world, &renderer.lights(&world), 0, &mut Sampler::from_seed(0)), Vec3::new(4.0, 4.0, 4.0));
+
+        let miss = Ray::new(Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(renderer.color(&miss, &world, &renderer.lights(&world), 0, &mut Sampler::from_seed(0)), Vec3::zero());
+    }
+
+    #[test]
+    fn test_light_sampling() {
+        // diffuse floor lit by a sphere light straight above, direct lighting only
+        let mut world = HitableList::default();
+        world.push(crate::Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0,
+            Box::new(crate::Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))));
+        world.push(crate::Sphere::new(Vec3::new(0.0, 2.0, 0.0), 0.5,
+            Box::new(crate::DiffuseLight::new(Vec3::new(4.0, 4.0, 4.0)))));
+
+        let mut renderer = Renderer::new(4, 2);
+        renderer.max_depth = 1;
+        renderer.environment = Box::new(crate::ConstantEnvironment::new(Vec3::zero()));
+
+        // radiance of the floor is albedo * L * sin^2 of the cone half angle
+        let expected = 0.5 * 4.0 * (0.5f32 / 2.0).powi(2);
+        let ray = Ray::new(Vec3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+
+        let estimate = |lights: &LightList| {
+            let n = 20_000;
+            let values = (0..n)
+                .map(|i| renderer.color(&ray, &world, lights, 0, &mut Sampler::new(0, 0, i)).x)
+                .collect::<Vec<_>>();
+            let mean = values.iter().sum::<f32>() / n as f32;
+            let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n as f32;
+            (mean, variance)
+        };
+
+        let lights = renderer.lights(&world);
+        assert_eq!(lights.len(), 1);
+        let (nee, nee_variance) = estimate(&lights);
+        let (bsdf, bsdf_variance) = estimate(&LightList::empty());
+
+        assert!((nee - expected).abs() < 0.03 * expected, "{} vs {}", nee, expected);
+        assert!((bsdf - expected).abs() < 0.1 * expected, "{} vs {}", bsdf, expected);
+        assert!(nee_variance < 0.1 * bsdf_variance, "{} vs {}", nee_variance, bsdf_variance);
+    }
+
+    #[test]
+    fn test_render_size() {
+        let mut renderer = Renderer::new(4, 2);
+        renderer.samples = 1;
+
+        let cam = Camera::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0),
+            90.0, renderer.aspect(), 0.0, 1.0);
+        let fb = renderer.render(&HitableList::default(), &cam);
+
+        assert_eq!((fb.width(), fb.height()), (4, 2));
+        assert_eq!(fb.pixels().len(), 8);
+    }
+
+    #[test]
+    fn test_save_float() {
+        let mut fb = FrameBuffer::new(3, 2);
+        for y in 0..2 {
+            for x in 0..3 {
+                fb.set(x, y, Vec3::new(x as f32 * 4.0, y as f32, 0.25));
+            }
+        }
+
+        let dir = std::env::temp_dir();
+        let path = dir.join("rtw_test_framebuffer.exr");
+        fb.save(&path).unwrap();
+        let image = exr::prelude::read_first_rgba_layer_from_file(&path,
+            |size, _| vec![(0.0, 0.0, 0.0); size.area()],
+            |pixels, pos, (r, g, b, _a): (f32, f32, f32, f32)| pixels[pos.y() * 3 + pos.x()] = (r, g, b))
+            .unwrap();
+        assert_eq!(image.layer_data.channel_data.pixels[2], (8.0, 0.0, 0.25));
+        assert_eq!(image.layer_data.channel_data.pixels[4], (4.0, 1.0, 0.25));
+
+        let path = dir.join("rtw_test_framebuffer.hdr");
+        fb.save(&path).unwrap();
+        let reader = io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let pixels = image::hdr::HDRDecoder::new(reader).unwrap().read_image_hdr().unwrap();
+        assert_eq!(pixels.len(), 6);
+        assert_eq!(pixels[2], image::Rgb([8.0, 0.0, 0.25]));
+
+        let path = dir.join("rtw_test_framebuffer.pfm");
+        fb.save(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        let header = b"PF\n3 2\n-1.0\n";
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(data.len(), header.len() + 3 * 2 * 12);
+
+        // first row in the file is the bottom row
+        let float = |i: usize| {
+            let o = header.len() + i * 4;
+            f32::from_le_bytes([data[o], data[o + 1], data[o + 2], data[o + 3]])
+        };
+        assert_eq!((float(6), float(7), float(8)), (8.0, 1.0, 0.25));
+        assert_eq!((float(15), float(16), float(17)), (8.0, 0.0, 0.25));
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut world = HitableList::default();
+        world.push(crate::Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5,
+            Box::new(crate::Lambertian::new(Vec3::new(0.8, 0.3, 0.3)))));
+        world.push(crate::Sphere::new(Vec3::new(0.0, -100.5, -1.0), 100.0,
+            Box::new(crate::Dielectric::new(1.5))));
+
+        let mut renderer = Renderer::new(8, 4);
+        renderer.samples = 16;
+        let cam = Camera::new(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0),
+            90.0, renderer.aspect(), 0.5, 2.0);
+
+        let a = renderer.render(&world, &cam);
+        let b = renderer.render(&world, &cam);
+        assert_eq!(a.pixels(), b.pixels());
+
+        renderer.seed = 1;
+        let c = renderer.render(&world, &cam);
+        assert_ne!(a.pixels(), c.pixels());
+
+        // tiled parallel rendering matches the serial path
+        renderer.seed = 0;
+        let mut serial = FrameBuffer::new(8, 4);
+        for y in 0..4 {
+            for x in 0..8 {
+                serial.set(x, y, renderer.render_pixel(&world, &renderer.lights(&world), &cam, x, y));
+            }
+        }
+        for &(size, order) in [(3, TileOrder::Scanline), (5, TileOrder::Spiral), (16, TileOrder::Spiral)].iter() {
+            renderer.tile_size = size;
+            renderer.tile_order = order;
+            assert_eq!(renderer.render(&world, &cam).pixels(), serial.pixels());
+        }
+    }
+
+    #[test]
+    fn test_tiles() {
+        let mut renderer = Renderer::new(10, 7);
+        renderer.tile_size = 4;
+
+        for &order in [TileOrder::Scanline, TileOrder::Spiral].iter() {
+            renderer.tile_order = order;
+            let tiles = renderer.tiles();
+            assert_eq!(tiles.len(), 6);
+            assert_eq!(tiles.iter().map(|t| t.width * t.height).sum::<u32>(), 70);
+        }
+
+        renderer.tile_order = TileOrder::Scanline;
+        assert_eq!(renderer.tiles()[0], Tile { x: 0, y: 0, width: 4, height: 4 });
+        assert_eq!(renderer.tiles()[5], Tile { x: 8, y: 4, width: 2, height: 3 });
+
+        // the center tile comes first
+        renderer = Renderer::new(48, 48);
+        assert_eq!(renderer.tiles()[0], Tile { x: 16, y: 16, width: 16, height: 16 });
+    }
+}
@@ -0,0 +1,71 @@
+use rand::prelude::*;
+use rand_pcg::Pcg32;
+
+/// splitmix64 finalizer, spreads nearby inputs over the whole range
+pub(crate) fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// explicit random number generator for rendering
+///
+/// every camera sample gets its own sampler seeded from the global seed, pixel index
+/// and sample index, so results do not depend on thread scheduling
+pub struct Sampler {
+    rng: Pcg32,
+}
+
+impl Sampler {
+    /// construct new sampler for `sample` of `pixel` under global `seed`
+    pub fn new(seed: u64, pixel: u64, sample: u32) -> Self {
+        let state = mix(seed ^ mix(pixel ^ mix(u64::from(sample))));
+        Sampler {
+            rng: Pcg32::new(state, mix(state)),
+        }
+    }
+
+    /// construct new sampler from a single seed, for things like scene generation
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new(seed, 0, 0)
+    }
+
+    /// uniform random number in [0.0, 1.0)
+    pub fn next_f32(&mut self) -> f32 {
+        self.rng.gen::<f32>()
+    }
+}
+
+impl RngCore for Sampler {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reproducible() {
+        let a = (0..8).map(|_| Sampler::new(1, 2, 3).next_u32()).collect::<Vec<_>>();
+        assert!(a.iter().all(|&x| x == a[0]));
+
+        assert_ne!(Sampler::new(1, 2, 3).next_u32(), Sampler::new(1, 2, 4).next_u32());
+        assert_ne!(Sampler::new(1, 2, 3).next_u32(), Sampler::new(1, 3, 3).next_u32());
+        assert_ne!(Sampler::new(1, 2, 3).next_u32(), Sampler::new(2, 2, 3).next_u32());
+    }
+}
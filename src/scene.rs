@@ -0,0 +1,855 @@
+//! declarative TOML scene description
+//!
+//! ```toml
+//! [render]            # optional, all fields optional
+//! width = 200
+//! height = 100
+//! samples = 100
+//! max_depth = 50
+//! light_sampling = true         # sample emissive spheres directly
+//! exposure = 0.0                # optional tone mapping, stops
+//! tonemap = "aces"              # "clamp" (default) / "reinhard" / "filmic" / "aces"
+//! transfer = "srgb"             # "gamma2" (default) / "srgb"
+//! dither = true                 # ordered dithering before 8-bit quantization
+//!
+//! [environment]       # optional, defaults to white to blue sky gradient
+//! type = "constant"             # or "gradient" with optional `bottom` / `top` colors
+//! color = [0.0, 0.0, 0.0]
+//! # type = "map"                # equirectangular .hdr / .exr, path is relative to scene file
+//! # path = "sky.hdr"
+//! # rotation = 90.0             # optional, degrees around y axis
+//! # intensity = 1.0             # optional
+//!
+//! [camera]
+//! look_from = [13.0, 2.0, 3.0]
+//! look_at = [0.0, 0.0, 0.0]
+//! vup = [0.0, 1.0, 0.0]         # optional, defaults to +y
+//! fov = 20.0
+//! aperture = 0.1                # optional, defaults to 0
+//! focus_dist = 10.0             # optional, defaults to distance to `look_at`
+//! shutter = [0.0, 1.0]          # optional open and close time for motion blur, defaults to [0, 0]
+//!
+//! [textures.checker]
+//! type = "checker"              # or "constant" / "image" / "noise" / "marble"
+//! odd = [0.2, 0.3, 0.1]
+//! even = [0.9, 0.9, 0.9]
+//! scale = 10.0
+//!
+//! [materials.ground]
+//! type = "lambertian"           # or "metal" / "dielectric" / "diffuse_light"
+//! albedo = "checker"            # texture name or color like [0.5, 0.5, 0.5]
+//!
+//! [materials.gold]
+//! type = "conductor"            # GGX metal, or "rough_dielectric" with `ref_index` and `roughness`
+//! preset = "gold"               # or "copper" / "aluminium", or complex index with `eta` and `k` colors
+//! roughness = 0.3
+//!
+//! [[spheres]]
+//! center = [0.0, -1000.0, 0.0]
+//! radius = 1000.0
+//! material = "ground"
+//! # center1 = [0.0, -999.0, 0.0] # optional, center at time 1 for moving spheres
+//!
+//! [[quads]]                     # parallelogram facing `u` x `v`
+//! corner = [0.0, 0.0, 0.0]
+//! u = [1.0, 0.0, 0.0]
+//! v = [0.0, 1.0, 0.0]
+//! material = "ground"
+//!
+//! [[boxes]]                     # axis-aligned, also `[[disks]]` with `center`, `normal`, `radius`
+//! min = [0.0, 0.0, 0.0]         # and infinite `[[planes]]` with `point` and `normal`
+//! max = [1.0, 1.0, 1.0]
+//! material = "ground"
+//!
+//! [[cylinders]]                 # also `[[cones]]` and `[[paraboloids]]`, around the +y axis through `base`
+//! base = [0.0, 0.0, 0.0]
+//! radius = 0.5
+//! height = 2.0
+//! material = "gold"
+//! # phi_max = 180.0             # optional, swept degrees from +x towards +z
+//! # open = true                 # optional, remove the caps
+//!
+//! [[tori]]                      # ring in the xz plane, also with optional `phi_max`
+//! center = [0.0, 1.0, 0.0]
+//! major_radius = 1.0
+//! minor_radius = 0.25
+//! material = "gold"
+//!
+//! [[models]]                    # wavefront OBJ, path is relative to scene file
+//! path = "bunny.obj"
+//! # scale = 2.0                 # optional, number or per-axis [x, y, z]
+//! # rotate = [0.0, 45.0, 0.0]   # optional, degrees around x, y then z
+//! # translate = [1.0, 0.0, 0.0] # optional, transformed models share geometry loaded once
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use toml::Value;
+
+use crate::{Mat4, Vec3};
+use crate::{CameraParams, Dielectric, DiffuseLight, HitableList, Lambertian, Material, Metal, Renderer, Sphere};
+use crate::{ToneOperator, Transfer};
+use crate::{BvhNode, Conductor, MovingSphere, RoughDielectric, Transformed};
+use crate::{Cuboid, Disk, Plane, Quad};
+use crate::{Cone, Cylinder, Paraboloid, Torus};
+use crate::{ConstantEnvironment, Environment, EnvironmentMap, GradientEnvironment};
+use crate::{CheckerTexture, ConstantTexture, ImageTexture, MarbleTexture, NoiseTexture, Texture};
+use crate::obj::{load_obj, ObjError};
+
+/// error while loading a scene file
+#[derive(Debug)]
+pub enum SceneError {
+    /// failed to read file
+    Io(PathBuf, std::io::Error),
+    /// invalid TOML syntax
+    Toml(toml::de::Error),
+    /// invalid scene description at `path` (like `materials.glass.type`)
+    Invalid { path: String, message: String },
+    /// failed to load referenced model
+    Model(ObjError),
+    /// failed to load referenced environment map
+    Environment(PathBuf, std::io::Error),
+    /// failed to load referenced image texture
+    Texture(PathBuf, std::io::Error),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            SceneError::Toml(e) => write!(f, "{}", e),
+            SceneError::Invalid { path, message } => write!(f, "{}: {}", path, message),
+            SceneError::Model(e) => write!(f, "{}", e),
+            SceneError::Environment(path, e) | SceneError::Texture(path, e) =>
+                write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// scene loaded from file, ready to render
+pub struct Scene {
+    /// all objects in scene
+    pub world: HitableList<'static>,
+    /// scene camera, built once the image aspect ratio is known
+    pub camera: CameraParams,
+    /// render settings
+    pub renderer: Renderer,
+}
+
+/// a TOML value with its path from the document root
+struct Node<'v> {
+    path: String,
+    value: &'v Value,
+}
+
+impl<'v> Node<'v> {
+    fn error<T, S: Into<String>>(&self, message: S) -> Result<T, SceneError> {
+        Err(SceneError::Invalid { path: self.path.clone(), message: message.into() })
+    }
+
+    fn child(&self, key: &str, value: &'v Value) -> Node<'v> {
+        let path = match self.path.is_empty() {
+            true => key.to_string(),
+            false => format!("{}.{}", self.path, key),
+        };
+        Node { path, value }
+    }
+
+    fn table(&self) -> Result<&'v toml::value::Table, SceneError> {
+        match self.value.as_table() {
+            Some(t) => Ok(t),
+            None => self.error(format!("expected table, found {}", self.value.type_str())),
+        }
+    }
+
+    /// reject keys not in `allowed`
+    fn check_keys(&self, allowed: &[&str]) -> Result<(), SceneError> {
+        match self.table()?.keys().find(|k| !allowed.contains(&k.as_str())) {
+            Some(k) => self.child(k, self.value).error("unknown field"),
+            None => Ok(()),
+        }
+    }
+
+    fn opt(&self, key: &str) -> Result<Option<Node<'v>>, SceneError> {
+        Ok(self.table()?.get(key).map(|v| self.child(key, v)))
+    }
+
+    fn get(&self, key: &str) -> Result<Node<'v>, SceneError> {
+        match self.opt(key)? {
+            Some(n) => Ok(n),
+            None => self.child(key, self.value).error("missing field"),
+        }
+    }
+
+    fn array(&self) -> Result<Vec<Node<'v>>, SceneError> {
+        match self.value.as_array() {
+            Some(a) => Ok(a.iter()
+                .enumerate()
+                .map(|(i, value)| Node { path: format!("{}[{}]", self.path, i), value })
+                .collect()),
+            None => self.error(format!("expected array, found {}", self.value.type_str())),
+        }
+    }
+
+    fn str(&self) -> Result<&'v str, SceneError> {
+        match self.value.as_str() {
+            Some(s) => Ok(s),
+            None => self.error(format!("expected string, found {}", self.value.type_str())),
+        }
+    }
+
+    fn f32(&self) -> Result<f32, SceneError> {
+        match self.value {
+            Value::Float(f) => Ok(*f as f32),
+            Value::Integer(i) => Ok(*i as f32),
+            v => self.error(format!("expected number, found {}", v.type_str())),
+        }
+    }
+
+    fn u32(&self) -> Result<u32, SceneError> {
+        match self.value.as_integer() {
+            Some(i) if i >= 0 && i <= i64::from(u32::MAX) => Ok(i as u32),
+            Some(i) => self.error(format!("{} is out of range", i)),
+            None => self.error(format!("expected integer, found {}", self.value.type_str())),
+        }
+    }
+
+    fn positive_u32(&self) -> Result<u32, SceneError> {
+        match self.u32()? {
+            0 => self.error("must be positive"),
+            n => Ok(n),
+        }
+    }
+
+    fn bool(&self) -> Result<bool, SceneError> {
+        match self.value.as_bool() {
+            Some(b) => Ok(b),
+            None => self.error(format!("expected boolean, found {}", self.value.type_str())),
+        }
+    }
+
+    fn vec3(&self) -> Result<Vec3, SceneError> {
+        let a = self.array()?;
+        if a.len() != 3 {
+            return self.error(format!("expected 3 numbers, found {}", a.len()));
+        }
+        Ok(Vec3::new(a[0].f32()?, a[1].f32()?, a[2].f32()?))
+    }
+
+    /// new instance of material by name
+    fn material(&self, materials: &Materials) -> Result<Box<dyn Material>, SceneError> {
+        let name = self.str()?;
+        match materials.get(name) {
+            Some(m) => Ok(m.build()),
+            None => self.error(format!("unknown material `{}`", name)),
+        }
+    }
+
+    /// texture by name or constant texture from color
+    fn texture(&self, textures: &Textures) -> Result<Arc<dyn Texture>, SceneError> {
+        match self.value {
+            Value::String(name) => match textures.get(name.as_str()) {
+                Some(t) => Ok(t.clone()),
+                None => self.error(format!("unknown texture `{}`", name)),
+            },
+            _ => Ok(Arc::new(ConstantTexture::new(self.vec3()?))),
+        }
+    }
+}
+
+type Textures<'v> = HashMap<&'v str, Arc<dyn Texture>>;
+type Materials<'v> = HashMap<&'v str, MaterialDef>;
+
+fn parse_texture(node: &Node, base_dir: &Path) -> Result<Arc<dyn Texture>, SceneError> {
+    let seed = |node: &Node| -> Result<u64, SceneError> {
+        Ok(match node.opt("seed")? {
+            Some(n) => u64::from(n.u32()?),
+            None => 0,
+        })
+    };
+
+    let kind = node.get("type")?;
+    let texture: Arc<dyn Texture> = match kind.str()? {
+        "constant" => {
+            node.check_keys(&["type", "color"])?;
+            Arc::new(ConstantTexture::new(node.get("color")?.vec3()?))
+        }
+        "checker" => {
+            node.check_keys(&["type", "odd", "even", "scale"])?;
+            Arc::new(CheckerTexture::new(
+                Arc::new(ConstantTexture::new(node.get("odd")?.vec3()?)),
+                Arc::new(ConstantTexture::new(node.get("even")?.vec3()?)),
+                node.get("scale")?.f32()?))
+        }
+        "image" => {
+            node.check_keys(&["type", "path"])?;
+            let path = base_dir.join(node.get("path")?.str()?);
+            Arc::new(ImageTexture::load(&path).map_err(|e| SceneError::Texture(path, e))?)
+        }
+        "noise" => {
+            node.check_keys(&["type", "scale", "seed"])?;
+            Arc::new(NoiseTexture::new(node.get("scale")?.f32()?, seed(node)?))
+        }
+        "marble" => {
+            node.check_keys(&["type", "scale", "seed"])?;
+            Arc::new(MarbleTexture::new(node.get("scale")?.f32()?, seed(node)?))
+        }
+        other => return kind.error(format!(
+            "unknown texture type `{}`, expected `constant`, `checker`, `image`, `noise` or `marble`", other)),
+    };
+
+    Ok(texture)
+}
+
+/// material definition, instantiated for every object referencing it
+enum MaterialDef {
+    Lambertian(Arc<dyn Texture>),
+    Metal(Arc<dyn Texture>, f32),
+    Dielectric(f32),
+    DiffuseLight(Arc<dyn Texture>),
+    Conductor(Conductor),
+    RoughDielectric(f32, f32),
+}
+
+impl MaterialDef {
+    fn parse(node: &Node, textures: &Textures) -> Result<Self, SceneError> {
+        let kind = node.get("type")?;
+        match kind.str()? {
+            "lambertian" => {
+                node.check_keys(&["type", "albedo"])?;
+                Ok(MaterialDef::Lambertian(node.get("albedo")?.texture(textures)?))
+            }
+            "metal" => {
+                node.check_keys(&["type", "albedo", "fuzz"])?;
+                let fuzz = match node.opt("fuzz")? {
+                    Some(n) => n.f32()?,
+                    None => 0.0,
+                };
+                Ok(MaterialDef::Metal(node.get("albedo")?.texture(textures)?, fuzz))
+            }
+            "dielectric" => {
+                node.check_keys(&["type", "ref_index"])?;
+                Ok(MaterialDef::Dielectric(node.get("ref_index")?.f32()?))
+            }
+            "diffuse_light" => {
+                node.check_keys(&["type", "emit"])?;
+                Ok(MaterialDef::DiffuseLight(node.get("emit")?.texture(textures)?))
+            }
+            "conductor" => {
+                node.check_keys(&["type", "preset", "eta", "k", "roughness"])?;
+                let roughness = match node.opt("roughness")? {
+                    Some(n) => n.f32()?,
+                    None => 0.0,
+                };
+                let conductor = match node.opt("preset")? {
+                    Some(n) => match Conductor::preset(n.str()?, roughness) {
+                        Some(c) => c,
+                        None => return n.error(format!(
+                            "unknown conductor `{}`, expected `gold`, `copper` or `aluminium`", n.str()?)),
+                    },
+                    None => Conductor::new(node.get("eta")?.vec3()?, node.get("k")?.vec3()?, roughness),
+                };
+                Ok(MaterialDef::Conductor(conductor))
+            }
+            "rough_dielectric" => {
+                node.check_keys(&["type", "ref_index", "roughness"])?;
+                Ok(MaterialDef::RoughDielectric(node.get("ref_index")?.f32()?, node.get("roughness")?.f32()?))
+            }
+            other => kind.error(format!(
+                "unknown material type `{}`, expected `lambertian`, `metal`, `dielectric`, `diffuse_light`, \
+                 `conductor` or `rough_dielectric`", other)),
+        }
+    }
+
+    fn build(&self) -> Box<dyn Material> {
+        match self {
+            MaterialDef::Lambertian(albedo) => Box::new(Lambertian::textured(albedo.clone())),
+            MaterialDef::Metal(albedo, fuzz) => Box::new(Metal::textured(albedo.clone(), *fuzz)),
+            MaterialDef::Dielectric(ref_index) => Box::new(Dielectric::new(*ref_index)),
+            MaterialDef::DiffuseLight(emit) => Box::new(DiffuseLight::textured(emit.clone())),
+            MaterialDef::Conductor(conductor) => Box::new(conductor.clone()),
+            MaterialDef::RoughDielectric(ref_index, roughness) => Box::new(RoughDielectric::new(*ref_index, *roughness)),
+        }
+    }
+}
+
+fn parse_renderer(root: &Node) -> Result<Renderer, SceneError> {
+    let mut renderer = Renderer::new(200, 100);
+
+    if let Some(node) = root.opt("render")? {
+        node.check_keys(&["width", "height", "samples", "max_depth", "t_min", "light_sampling",
+            "exposure", "tonemap", "transfer", "dither"])?;
+        if let Some(n) = node.opt("width")? {
+            renderer.width = n.positive_u32()?;
+        }
+        if let Some(n) = node.opt("height")? {
+            renderer.height = n.positive_u32()?;
+        }
+        if let Some(n) = node.opt("samples")? {
+            renderer.samples = n.positive_u32()?;
+        }
+        if let Some(n) = node.opt("max_depth")? {
+            renderer.max_depth = n.u32()?;
+        }
+        if let Some(n) = node.opt("t_min")? {
+            renderer.t_min = n.f32()?;
+        }
+        if let Some(n) = node.opt("light_sampling")? {
+            renderer.light_sampling = n.bool()?;
+        }
+        if let Some(n) = node.opt("exposure")? {
+            renderer.tone_mapping.exposure = n.f32()?;
+        }
+        if let Some(n) = node.opt("tonemap")? {
+            renderer.tone_mapping.operator = match n.str()? {
+                "clamp" => ToneOperator::Clamp,
+                "reinhard" => ToneOperator::Reinhard,
+                "filmic" => ToneOperator::Filmic,
+                "aces" => ToneOperator::Aces,
+                other => return n.error(format!(
+                    "unknown tone mapping `{}`, expected `clamp`, `reinhard`, `filmic` or `aces`", other)),
+            };
+        }
+        if let Some(n) = node.opt("transfer")? {
+            renderer.tone_mapping.transfer = match n.str()? {
+                "gamma2" => Transfer::Gamma2,
+                "srgb" => Transfer::Srgb,
+                other => return n.error(format!("unknown transfer `{}`, expected `gamma2` or `srgb`", other)),
+            };
+        }
+        if let Some(n) = node.opt("dither")? {
+            renderer.tone_mapping.dither = n.bool()?;
+        }
+    }
+
+    Ok(renderer)
+}
+
+fn parse_environment(root: &Node, base_dir: &Path) -> Result<Option<Box<dyn Environment>>, SceneError> {
+    let node = match root.opt("environment")? {
+        Some(n) => n,
+        None => return Ok(None),
+    };
+
+    let kind = node.get("type")?;
+    let environment: Box<dyn Environment> = match kind.str()? {
+        "constant" => {
+            node.check_keys(&["type", "color"])?;
+            Box::new(ConstantEnvironment::new(node.get("color")?.vec3()?))
+        }
+        "gradient" => {
+            node.check_keys(&["type", "bottom", "top"])?;
+            let mut gradient = GradientEnvironment::default();
+            if let Some(n) = node.opt("bottom")? {
+                gradient.bottom = n.vec3()?;
+            }
+            if let Some(n) = node.opt("top")? {
+                gradient.top = n.vec3()?;
+            }
+            Box::new(gradient)
+        }
+        "map" => {
+            node.check_keys(&["type", "path", "rotation", "intensity"])?;
+            let path = base_dir.join(node.get("path")?.str()?);
+            let mut map = EnvironmentMap::load(&path).map_err(|e| SceneError::Environment(path, e))?;
+            if let Some(n) = node.opt("rotation")? {
+                map.rotation = n.f32()?;
+            }
+            if let Some(n) = node.opt("intensity")? {
+                map.intensity = n.f32()?;
+            }
+            Box::new(map)
+        }
+        other => return kind.error(format!(
+            "unknown environment type `{}`, expected `constant`, `gradient` or `map`", other)),
+    };
+
+    Ok(Some(environment))
+}
+
+fn parse_camera(root: &Node) -> Result<CameraParams, SceneError> {
+    let node = root.get("camera")?;
+    node.check_keys(&["look_from", "look_at", "vup", "fov", "aperture", "focus_dist", "shutter"])?;
+
+    let look_from = node.get("look_from")?.vec3()?;
+    let look_at = node.get("look_at")?.vec3()?;
+    let vup = match node.opt("vup")? {
+        Some(n) => n.vec3()?,
+        None => Vec3::new(0.0, 1.0, 0.0),
+    };
+    let fov = node.get("fov")?.f32()?;
+    let aperture = match node.opt("aperture")? {
+        Some(n) => n.f32()?,
+        None => 0.0,
+    };
+    let focus_dist = match node.opt("focus_dist")? {
+        Some(n) => n.f32()?,
+        None => (look_from - look_at).norm(),
+    };
+
+    let (shutter_open, shutter_close) = match node.opt("shutter")? {
+        Some(n) => {
+            let a = n.array()?;
+            if a.len() != 2 {
+                return n.error(format!("expected 2 numbers, found {}", a.len()));
+            }
+            let (open, close) = (a[0].f32()?, a[1].f32()?);
+            if !(0.0 <= open && open <= close) {
+                return n.error("expected 0 <= open <= close");
+            }
+            (open, close)
+        }
+        None => (0.0, 0.0),
+    };
+
+    Ok(CameraParams { look_from, look_at, vup, fov, aperture, focus_dist, shutter_open, shutter_close })
+}
+
+/// optional `scale`, `rotate` (degrees around x, y then z) and `translate` of a model, applied in that order
+fn parse_transform(node: &Node) -> Result<Option<Mat4>, SceneError> {
+    let mut transform = None;
+    let mut then = |t: Mat4| transform = Some(t * transform.unwrap_or_default());
+
+    if let Some(scale) = node.opt("scale")? {
+        match scale.value {
+            Value::Array(_) => then(Mat4::scaling(scale.vec3()?)),
+            _ => then(Mat4::scaling(Vec3::unit() * scale.f32()?)),
+        }
+    }
+    if let Some(rotate) = node.opt("rotate")? {
+        let angles = rotate.vec3()?;
+        for axis in 0..3 {
+            let mut dir = Vec3::zero();
+            dir[axis] = 1.0;
+            then(Mat4::rotation(dir, angles[axis]));
+        }
+    }
+    if let Some(translate) = node.opt("translate")? {
+        then(Mat4::translation(translate.vec3()?));
+    }
+
+    match transform {
+        Some(t) if t.inverse().is_none() => node.child("scale", node.value).error("singular transform"),
+        t => Ok(t),
+    }
+}
+
+/// parse scene from TOML `source`, model paths are relative to `base_dir`
+pub fn parse_scene(source: &str, base_dir: &Path) -> Result<Scene, SceneError> {
+    let value = source.parse::<Value>().map_err(SceneError::Toml)?;
+    let root = Node { path: String::new(), value: &value };
+    root.check_keys(&["render", "environment", "camera", "textures", "materials", "spheres", "quads", "boxes",
+                      "disks", "planes", "cylinders", "cones", "paraboloids", "tori", "models"])?;
+
+    let mut renderer = parse_renderer(&root)?;
+    if let Some(environment) = parse_environment(&root, base_dir)? {
+        renderer.environment = environment;
+    }
+    let camera = parse_camera(&root)?;
+
+    let mut textures = HashMap::new();
+    if let Some(node) = root.opt("textures")? {
+        for (name, value) in node.table()? {
+            textures.insert(name.as_str(), parse_texture(&node.child(name, value), base_dir)?);
+        }
+    }
+
+    let mut materials = HashMap::new();
+    if let Some(node) = root.opt("materials")? {
+        for (name, value) in node.table()? {
+            materials.insert(name.as_str(), MaterialDef::parse(&node.child(name, value), &textures)?);
+        }
+    }
+
+    let mut world = HitableList::default();
+
+    if let Some(node) = root.opt("spheres")? {
+        for sphere in node.array()? {
+            sphere.check_keys(&["center", "center1", "radius", "material"])?;
+            let material = sphere.get("material")?.material(&materials)?;
+            let (center, radius) = (sphere.get("center")?.vec3()?, sphere.get("radius")?.f32()?);
+            match sphere.opt("center1")? {
+                Some(n) => world.push(MovingSphere::new(center, n.vec3()?, 0.0, 1.0, radius, material)),
+                None => world.push(Sphere::new(center, radius, material)),
+            }
+        }
+    }
+
+    if let Some(node) = root.opt("quads")? {
+        for quad in node.array()? {
+            quad.check_keys(&["corner", "u", "v", "material"])?;
+            let material = Arc::from(quad.get("material")?.material(&materials)?);
+            world.push(Quad::new(quad.get("corner")?.vec3()?, quad.get("u")?.vec3()?, quad.get("v")?.vec3()?, material));
+        }
+    }
+
+    if let Some(node) = root.opt("boxes")? {
+        for cuboid in node.array()? {
+            cuboid.check_keys(&["min", "max", "material"])?;
+            let material = Arc::from(cuboid.get("material")?.material(&materials)?);
+            world.push(Cuboid::new(cuboid.get("min")?.vec3()?, cuboid.get("max")?.vec3()?, material));
+        }
+    }
+
+    if let Some(node) = root.opt("disks")? {
+        for disk in node.array()? {
+            disk.check_keys(&["center", "normal", "radius", "material"])?;
+            let material = Arc::from(disk.get("material")?.material(&materials)?);
+            world.push(Disk::new(disk.get("center")?.vec3()?, disk.get("normal")?.vec3()?,
+                                 disk.get("radius")?.f32()?, material));
+        }
+    }
+
+    if let Some(node) = root.opt("planes")? {
+        for plane in node.array()? {
+            plane.check_keys(&["point", "normal", "material"])?;
+            let material = Arc::from(plane.get("material")?.material(&materials)?);
+            world.push(Plane::new(plane.get("point")?.vec3()?, plane.get("normal")?.vec3()?, material));
+        }
+    }
+
+    for kind in &["cylinders", "cones", "paraboloids"] {
+        if let Some(node) = root.opt(kind)? {
+            for shape in node.array()? {
+                shape.check_keys(&["base", "radius", "height", "phi_max", "open", "material"])?;
+                let material = shape.get("material")?.material(&materials)?;
+                let (base, radius, height) = (shape.get("base")?.vec3()?, shape.get("radius")?.f32()?,
+                                              shape.get("height")?.f32()?);
+                let phi_max = shape.opt("phi_max")?.map_or(Ok(360.0), |n| n.f32())?;
+                let open = shape.opt("open")?.map_or(Ok(false), |n| n.bool())?;
+
+                macro_rules! push {
+                    ($shape:expr) => {
+                        match open {
+                            true => world.push($shape.with_phi_max(phi_max).open()),
+                            false => world.push($shape.with_phi_max(phi_max)),
+                        }
+                    };
+                }
+                match *kind {
+                    "cylinders" => push!(Cylinder::new(base, radius, height, material)),
+                    "cones" => push!(Cone::new(base, radius, height, material)),
+                    _ => push!(Paraboloid::new(base, radius, height, material)),
+                }
+            }
+        }
+    }
+
+    if let Some(node) = root.opt("tori")? {
+        for torus in node.array()? {
+            torus.check_keys(&["center", "major_radius", "minor_radius", "phi_max", "material"])?;
+            let material = torus.get("material")?.material(&materials)?;
+            let phi_max = torus.opt("phi_max")?.map_or(Ok(360.0), |n| n.f32())?;
+            world.push(Torus::new(torus.get("center")?.vec3()?, torus.get("major_radius")?.f32()?,
+                                  torus.get("minor_radius")?.f32()?, material).with_phi_max(phi_max));
+        }
+    }
+
+    if let Some(node) = root.opt("models")? {
+        // transformed models are instances sharing geometry loaded once per path
+        let mut instanced: HashMap<PathBuf, Arc<BvhNode<'static>>> = HashMap::new();
+
+        for model in node.array()? {
+            model.check_keys(&["path", "translate", "rotate", "scale"])?;
+            let path = base_dir.join(model.get("path")?.str()?);
+            let transform = match parse_transform(&model)? {
+                Some(transform) => transform,
+                None => {
+                    world.append(load_obj(path).map_err(SceneError::Model)?);
+                    continue;
+                }
+            };
+
+            let object = match instanced.get(&path) {
+                Some(object) => object.clone(),
+                None => {
+                    let list = load_obj(&path).map_err(SceneError::Model)?;
+                    if list.is_empty() {
+                        return model.error("model has no faces to instance");
+                    }
+                    let object = Arc::new(BvhNode::new(list));
+                    instanced.insert(path, object.clone());
+                    object
+                }
+            };
+            world.push(Transformed::new(object, transform));
+        }
+    }
+
+    Ok(Scene { world, camera, renderer })
+}
+
+/// load scene from TOML file
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| SceneError::Io(path.to_owned(), e))?;
+    parse_scene(&source, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Hitable;
+
+    const SCENE: &str = r#"
+        [render]
+        width = 40
+        height = 20
+        samples = 4
+
+        [camera]
+        look_from = [0, 0, 0]
+        look_at = [0, 0, -1]
+        fov = 90
+
+        [textures.marble]
+        type = "marble"
+        scale = 4
+
+        [materials.red]
+        type = "lambertian"
+        albedo = [0.8, 0.3, 0.3]
+
+        [materials.stone]
+        type = "metal"
+        albedo = "marble"
+
+        [materials.glass]
+        type = "dielectric"
+        ref_index = 1.5
+
+        [[spheres]]
+        center = [0, 0, -1]
+        radius = 0.5
+        material = "red"
+
+        [[spheres]]
+        center = [0, -100.5, -1]
+        radius = 100
+        material = "glass"
+    "#;
+
+    fn error(source: &str) -> String {
+        parse_scene(source, Path::new("")).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_scene() {
+        let scene = parse_scene(SCENE, Path::new("")).unwrap();
+        assert_eq!(scene.world.len(), 2);
+        assert_eq!((scene.camera.shutter_open, scene.camera.shutter_close), (0.0, 0.0));
+        assert_eq!((scene.renderer.width, scene.renderer.height, scene.renderer.samples), (40, 20, 4));
+        assert_eq!(scene.renderer.max_depth, 50);
+        assert_eq!(scene.renderer.tone_mapping, crate::ToneMapping::default());
+
+        let source = format!("{}\n[materials.gold]\ntype = \"conductor\"\npreset = \"gold\"\nroughness = 0.2\n\
+            [materials.frosted]\ntype = \"rough_dielectric\"\nref_index = 1.5\nroughness = 0.3\n\
+            [materials.custom]\ntype = \"conductor\"\neta = [1, 1, 1]\nk = [3, 3, 3]", SCENE);
+        parse_scene(&source, Path::new("")).unwrap();
+
+        let source = SCENE.replace("samples = 4", "samples = 4\nexposure = -0.5\ntonemap = \"aces\"\ntransfer = \"srgb\"\ndither = true");
+        let tm = parse_scene(&source, Path::new("")).unwrap().renderer.tone_mapping;
+        assert_eq!(tm, crate::ToneMapping { exposure: -0.5, operator: ToneOperator::Aces, transfer: Transfer::Srgb, dither: true });
+
+        let source = SCENE.replace("fov = 90", "fov = 90\nshutter = [0.0, 0.5]")
+            .replace("radius = 0.5", "radius = 0.5\ncenter1 = [0, 1, -1]");
+        let scene = parse_scene(&source, Path::new("")).unwrap();
+        assert_eq!((scene.camera.shutter_open, scene.camera.shutter_close), (0.0, 0.5));
+        assert_eq!(scene.world.bounding_box().unwrap().max.y, 1.5);
+    }
+
+    #[test]
+    fn test_planar() {
+        let source = format!("{}\n[[quads]]\ncorner = [-1, -1, -2]\nu = [2, 0, 0]\nv = [0, 2, 0]\nmaterial = \"red\"\n\
+            [[boxes]]\nmin = [0, 0, -5]\nmax = [1, 1, -4]\nmaterial = \"red\"\n\
+            [[disks]]\ncenter = [0, 0, -6]\nnormal = [0, 0, 1]\nradius = 1\nmaterial = \"glass\"\n\
+            [[planes]]\npoint = [0, -1, 0]\nnormal = [0, 1, 0]\nmaterial = \"red\"", SCENE);
+        let scene = parse_scene(&source, Path::new("")).unwrap();
+        assert_eq!(scene.world.len(), 6);
+        assert!(scene.world.bounding_box().is_none());
+
+        assert_eq!(error(&source.replace("u = [2, 0, 0]", "u = 2")),
+            "quads[0].u: expected array, found integer");
+        assert_eq!(error(&source.replace("radius = 1\n", "")),
+            "disks[0].radius: missing field");
+
+        let source = format!("{}\n[[cylinders]]\nbase = [0, 0, -3]\nradius = 1\nheight = 2\nopen = true\nmaterial = \"red\"\n\
+            [[cones]]\nbase = [0, 0, -3]\nradius = 1\nheight = 2\nphi_max = 90\nmaterial = \"red\"\n\
+            [[paraboloids]]\nbase = [0, 0, -3]\nradius = 1\nheight = 2\nmaterial = \"red\"\n\
+            [[tori]]\ncenter = [0, 0, -3]\nmajor_radius = 1\nminor_radius = 0.2\nmaterial = \"red\"", SCENE);
+        assert_eq!(parse_scene(&source, Path::new("")).unwrap().world.len(), 6);
+        assert_eq!(error(&source.replace("open = true", "open = 1")),
+            "cylinders[0].open: expected boolean, found integer");
+    }
+
+    #[test]
+    fn test_model_instances() {
+        let dir = std::env::temp_dir();
+        std::fs::write(dir.join("rtw_test_instance.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+
+        let source = format!("{}\n[[models]]\npath = \"rtw_test_instance.obj\"\n\
+            [[models]]\npath = \"rtw_test_instance.obj\"\nscale = 2\nrotate = [0, 0, 90]\ntranslate = [0, 0, -3]", SCENE);
+        let scene = parse_scene(&source, &dir).unwrap();
+        assert_eq!(scene.world.len(), 4);
+
+        // the instance is the rotated triangle (0, 0), (0, 2), (-2, 0) at z = -3
+        let ray = |x: f32| crate::Ray::new(Vec3::new(x, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!((scene.world.hit(&ray(-0.5), 0.001, f32::MAX).unwrap().t - 4.0).abs() < 1e-5);
+        assert!(scene.world.hit(&ray(1.5), 0.001, f32::MAX).is_none());
+
+        let singular = parse_scene(&source.replace("scale = 2", "scale = 0"), &dir).err().unwrap();
+        assert_eq!(singular.to_string(), "models[1].scale: singular transform");
+
+        std::fs::write(dir.join("rtw_test_empty.obj"), "v 0 0 0\n").unwrap();
+        let source = format!("{}\n[[models]]\npath = \"rtw_test_empty.obj\"\n\
+            [[models]]\npath = \"rtw_test_empty.obj\"\ntranslate = [0, 0, -3]", SCENE);
+        let empty = parse_scene(&source, &dir).err().unwrap();
+        assert_eq!(empty.to_string(), "models[1]: model has no faces to instance");
+    }
+
+    #[test]
+    fn test_load_scene() {
+        let scene = load_scene(concat!(env!("CARGO_MANIFEST_DIR"), "/scenes/ch10_11.toml")).unwrap();
+        assert_eq!(scene.world.len(), 5);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error(&SCENE.replace("\"dielectric\"", "\"glas\"")),
+            "materials.glass.type: unknown material type `glas`, expected `lambertian`, `metal`, `dielectric`, \
+             `diffuse_light`, `conductor` or `rough_dielectric`");
+        assert_eq!(error(&SCENE.replace("ref_index = 1.5", "")),
+            "materials.glass.ref_index: missing field");
+        assert_eq!(error(&SCENE.replace("material = \"glass\"", "material = \"metal\"")),
+            "spheres[1].material: unknown material `metal`");
+        assert_eq!(error(&SCENE.replace("radius = 0.5", "radius = \"big\"")),
+            "spheres[0].radius: expected number, found string");
+        assert_eq!(error(&SCENE.replace("look_at = [0, 0, -1]", "look_at = [0, -1]")),
+            "camera.look_at: expected 3 numbers, found 2");
+        assert_eq!(error(&SCENE.replace("fov = 90", "fov = 90\nzoom = 2")),
+            "camera.zoom: unknown field");
+        assert_eq!(error(&SCENE.replace("albedo = \"marble\"", "albedo = \"granite\"")),
+            "materials.stone.albedo: unknown texture `granite`");
+        assert_eq!(error(&format!("{}\n[environment]\ntype = \"sky\"", SCENE)),
+            "environment.type: unknown environment type `sky`, expected `constant`, `gradient` or `map`");
+        assert_eq!(error(&format!("{}\n[materials.gold]\ntype = \"conductor\"\npreset = \"silver\"", SCENE)),
+            "materials.gold.preset: unknown conductor `silver`, expected `gold`, `copper` or `aluminium`");
+        assert_eq!(error(&SCENE.replace("samples = 4", "samples = 4\ntonemap = \"hable\"")),
+            "render.tonemap: unknown tone mapping `hable`, expected `clamp`, `reinhard`, `filmic` or `aces`");
+        assert_eq!(error(&SCENE.replace("samples = 4", "samples = 4\ndither = 1")),
+            "render.dither: expected boolean, found integer");
+        assert_eq!(error(&SCENE.replace("samples = 4", "samples = 0")),
+            "render.samples: must be positive");
+        assert_eq!(error(&SCENE.replace("width = 40", "width = 0")),
+            "render.width: must be positive");
+        assert_eq!(error(&SCENE.replace("fov = 90", "fov = 90\nshutter = [1.0, 0.5]")),
+            "camera.shutter: expected 0 <= open <= close");
+        assert_eq!(error(&SCENE.replace("fov = 90", "fov = 90\nshutter = [-1.0, 0.0]")),
+            "camera.shutter: expected 0 <= open <= close");
+    }
+}
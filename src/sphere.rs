@@ -1,6 +1,8 @@
+use std::f32::consts::PI;
+
 use crate::Ray;
 use crate::Vec3;
-use crate::{HitRecord, Hitable, Material};
+use crate::{Aabb, HitRecord, Hitable, Material, Sampler, Solid};
 
 /// sphere hitable object
 pub struct Sphere {
@@ -18,38 +20,196 @@ impl Sphere {
             material
         }
     }
+
+    /// cosine of the half angle of the cone subtended from `origin`, `None` inside the sphere
+    fn cone_cos(&self, origin: Vec3) -> Option<f32> {
+        let dist_squared = (self.center - origin).norm_squared();
+        let r_squared = self.radius * self.radius;
+        if dist_squared <= r_squared {
+            return None;
+        }
+        Some((1.0 - r_squared / dist_squared).sqrt())
+    }
+}
+
+/// closest intersection of `ray` with sphere in (`t_min`, `t_max`)
+fn hit_sphere<'a>(center: Vec3, radius: f32, material: &'a dyn Material,
+                  ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'a>> {
+    let oc = ray.origin - center;
+    let coeff_a = ray.direction.dot(ray.direction);
+    let coeff_b = oc.dot(ray.direction);
+    let coeff_c = oc.dot(oc) - radius * radius;
+    let discriminant = coeff_b * coeff_b - coeff_a * coeff_c;
+
+    if discriminant <= 0.0 {
+        return None;
+    }
+
+    let record = |t: f32| {
+        let point = ray.point_at(t);
+        let normal = (point - center) / radius;
+        let (u, v) = sphere_uv((point - center) / radius.abs());
+
+        HitRecord {
+            t,
+            point,
+            normal,
+            u,
+            v,
+            material,
+        }
+    };
+
+    let temp = (-coeff_b - discriminant.sqrt()) / coeff_a;
+    if temp < t_max && temp > t_min {
+        return Some(record(temp));
+    }
+
+    let temp = (-coeff_b + discriminant.sqrt()) / coeff_a;
+    if temp < t_max && temp > t_min {
+        return Some(record(temp));
+    }
+
+    None
+}
+
+/// uv coordinates of point `p` on unit sphere, u goes around y axis from -x, v goes from bottom to top
+pub(crate) fn sphere_uv(p: Vec3) -> (f32, f32) {
+    let phi = p.z.atan2(p.x);
+    let theta = p.y.clamp(-1.0, 1.0).asin();
+    (1.0 - (phi + PI) / (2.0 * PI), (theta + PI / 2.0) / PI)
 }
 
 impl Hitable for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-        let oc = ray.origin - self.center;
-        let coeff_a = ray.direction.dot(ray.direction);
-        let coeff_b = oc.dot(ray.direction);
-        let coeff_c = oc.dot(oc) - self.radius * self.radius;
-        let discriminant = coeff_b * coeff_b - coeff_a * coeff_c;
-
-        if discriminant > 0.0 {
-            let temp = (-coeff_b - discriminant.sqrt()) / coeff_a;
-            if temp < t_max && temp > t_min {
-                return Some(HitRecord {
-                    t: temp,
-                    point: ray.point_at(temp),
-                    normal: (ray.point_at(temp) - self.center) / self.radius,
-                    material: self.material.as_ref()
-                });
-            }
-
-            let temp = (-coeff_b + discriminant.sqrt()) / coeff_a;
-            if temp < t_max && temp > t_min {
-                return Some(HitRecord {
-                    t: temp,
-                    point: ray.point_at(temp),
-                    normal: (ray.point_at(temp) - self.center) / self.radius,
-                    material: self.material.as_ref()
-                });
-            }
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        hit_sphere(self.center, self.radius, self.material.as_ref(), ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::unit() * self.radius.abs();
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f32 {
+        match self.cone_cos(origin) {
+            Some(cos_max) if self.hit(&Ray::new(origin, direction), 0.0, f32::MAX).is_some() =>
+                1.0 / (2.0 * PI * (1.0 - cos_max)),
+            _ => 0.0,
+        }
+    }
+
+    fn random_direction(&self, origin: Vec3, sampler: &mut Sampler) -> Vec3 {
+        // uniform in the cone of directions subtended by the sphere
+        let cos_max = self.cone_cos(origin).unwrap_or(-1.0);
+        let (r1, r2) = (sampler.next_f32(), sampler.next_f32());
+        let z = 1.0 + r2 * (cos_max - 1.0);
+        let phi = 2.0 * PI * r1;
+        let sin = (1.0 - z * z).max(0.0).sqrt();
+
+        (self.center - origin).normalize().from_local(Vec3::new(phi.cos() * sin, phi.sin() * sin, z))
+    }
+
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.material.as_ref());
+    }
+}
+
+impl Solid for Sphere {}
+
+/// sphere moving linearly from `center0` at `time0` to `center1` at `time1`
+pub struct MovingSphere {
+    center0: Vec3,
+    center1: Vec3,
+    time0: f32,
+    time1: f32,
+    radius: f32,
+    material: Box<dyn Material>,
+}
+
+impl MovingSphere {
+    /// construct new moving sphere, it rests at `center0` before `time0` and at `center1` after `time1`
+    pub fn new(center0: Vec3, center1: Vec3, time0: f32, time1: f32, radius: f32,
+               material: Box<dyn Material>) -> Self {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
         }
+    }
+
+    /// sphere center at `time`, always within the bounding box
+    pub fn center(&self, time: f32) -> Vec3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        let s = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + s * (self.center1 - self.center0)
+    }
+}
+
+impl Hitable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        hit_sphere(self.center(ray.time), self.radius, self.material.as_ref(), ray, t_min, t_max)
+    }
+
+    /// bounds of the whole motion
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::unit() * self.radius.abs();
+        Some(Aabb::surrounding(
+            &Aabb::new(self.center0 - r, self.center0 + r),
+            &Aabb::new(self.center1 - r, self.center1 + r),
+        ))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.material.as_ref());
+    }
+}
+
+impl Solid for MovingSphere {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Lambertian;
+
+    #[test]
+    fn test_hit_uv() {
+        let s = Sphere::new(Vec3::new(0.0, 0.0, -2.0), 1.0, Box::new(Lambertian::new(Vec3::unit())));
+
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        let rec = s.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(rec.t, 1.0);
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!((rec.u - 0.25).abs() < 1e-6);
+        assert!((rec.v - 0.5).abs() < 1e-6);
+
+        let (_, v) = sphere_uv(Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(v, 1.0);
+    }
+
+    #[test]
+    fn test_moving_sphere() {
+        let s = MovingSphere::new(Vec3::new(0.0, 0.0, -2.0), Vec3::new(2.0, 0.0, -2.0), 0.0, 1.0, 0.5,
+            Box::new(Lambertian::new(Vec3::unit())));
+        assert_eq!(s.center(0.5), Vec3::new(1.0, 0.0, -2.0));
+        assert_eq!((s.center(-1.0), s.center(2.0)), (Vec3::new(0.0, 0.0, -2.0), Vec3::new(2.0, 0.0, -2.0)));
+
+        let ray = |x: f32, time: f32| Ray::with_time(Vec3::new(x, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), time);
+        assert_eq!(s.hit(&ray(0.0, 0.0), 0.001, f32::MAX).unwrap().t, 1.5);
+        assert!(s.hit(&ray(0.0, 1.0), 0.001, f32::MAX).is_none());
+        assert_eq!(s.hit(&ray(2.0, 1.0), 0.001, f32::MAX).unwrap().normal, Vec3::new(0.0, 0.0, 1.0));
 
-        None
+        let bbox = s.bounding_box().unwrap();
+        assert_eq!((bbox.min, bbox.max), (Vec3::new(-0.5, -0.5, -2.5), Vec3::new(2.5, 0.5, -1.5)));
     }
 }
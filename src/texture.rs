@@ -0,0 +1,278 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use rand::prelude::*;
+
+use crate::{Color, Sampler, Vec3};
+
+/// surface color lookup by surface coordinates (`u`, `v`) and hit point `p`
+pub trait Texture: Send + Sync {
+    /// texture color at (`u`, `v`) and point `p`
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Color;
+}
+
+/// single color texture
+#[derive(Clone)]
+pub struct ConstantTexture {
+    /// texture color
+    pub color: Color,
+}
+
+impl ConstantTexture {
+    /// construct new constant texture
+    pub fn new(color: Color) -> Self {
+        ConstantTexture { color }
+    }
+}
+
+impl Texture for ConstantTexture {
+    fn value(&self, _: f32, _: f32, _: Vec3) -> Color {
+        self.color
+    }
+}
+
+/// 3D checker pattern alternating two textures
+#[derive(Clone)]
+pub struct CheckerTexture {
+    /// texture of odd cells
+    pub odd: Arc<dyn Texture>,
+    /// texture of even cells
+    pub even: Arc<dyn Texture>,
+    /// checker frequency, cells are `PI / scale` wide
+    pub scale: f32,
+}
+
+impl CheckerTexture {
+    /// construct new checker texture
+    pub fn new(odd: Arc<dyn Texture>, even: Arc<dyn Texture>, scale: f32) -> Self {
+        CheckerTexture { odd, even, scale }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Color {
+        let sines = (self.scale * p.x).sin() * (self.scale * p.y).sin() * (self.scale * p.z).sin();
+        match sines < 0.0 {
+            true => self.odd.value(u, v, p),
+            false => self.even.value(u, v, p),
+        }
+    }
+}
+
+fn invalid_data<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// load 8-bit image and convert sRGB colors to linear, row major from the top row
+pub(crate) fn load_linear_rgb(path: &Path) -> io::Result<(usize, usize, Vec<Color>)> {
+    let image = image::open(path).map_err(invalid_data)?.to_rgb();
+    let linear = |c: u8| (f32::from(c) / 255.0).powf(2.2);
+    let pixels = image.pixels()
+        .map(|p| Vec3::new(linear(p[0]), linear(p[1]), linear(p[2])))
+        .collect();
+
+    Ok((image.width() as usize, image.height() as usize, pixels))
+}
+
+/// image texture, `v` goes from bottom to top
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    /// construct new image texture from row major `pixels` starting at the top row,
+    /// panics if the image is empty
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "empty image texture");
+        assert_eq!(pixels.len(), width * height, "pixel count mismatch");
+
+        ImageTexture { width, height, pixels }
+    }
+
+    /// load image file, colors are converted from sRGB to linear
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (width, height, pixels) = load_linear_rgb(path.as_ref())?;
+        if width == 0 || height == 0 {
+            return Err(invalid_data("empty image texture"));
+        }
+        Ok(Self::new(width, height, pixels))
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f32, v: f32, _: Vec3) -> Color {
+        let x = (u.clamp(0.0, 1.0) * self.width as f32) as usize;
+        let y = ((1.0 - v.clamp(0.0, 1.0)) * self.height as f32) as usize;
+
+        self.pixels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+}
+
+/// perlin gradient noise
+struct Perlin {
+    gradients: Vec<Vec3>,
+    perm: [Vec<usize>; 3],
+}
+
+impl Perlin {
+    const POINT_COUNT: usize = 256;
+
+    fn new(seed: u64) -> Self {
+        let mut rng = Sampler::from_seed(seed);
+        let gradients = (0..Self::POINT_COUNT)
+            .map(|_| Vec3::new(
+                2.0 * rng.gen::<f32>() - 1.0,
+                2.0 * rng.gen::<f32>() - 1.0,
+                2.0 * rng.gen::<f32>() - 1.0,
+            ).normalize())
+            .collect();
+
+        let mut perm = || {
+            let mut p = (0..Self::POINT_COUNT).collect::<Vec<_>>();
+            p.shuffle(&mut rng);
+            p
+        };
+
+        Perlin {
+            gradients,
+            perm: [perm(), perm(), perm()],
+        }
+    }
+
+    /// noise value in [-1.0, 1.0]
+    fn noise(&self, p: Vec3) -> f32 {
+        let (fx, fy, fz) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (u, v, w) = (p.x - fx, p.y - fy, p.z - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+
+        // hermite smoothing
+        let (uu, vv, ww) = (u * u * (3.0 - 2.0 * u), v * v * (3.0 - 2.0 * v), w * w * (3.0 - 2.0 * w));
+
+        let mut acc = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = |a: i64, d: i64| ((a + d) & (Self::POINT_COUNT as i64 - 1)) as usize;
+                    let g = self.gradients[
+                        self.perm[0][index(i, di)] ^
+                        self.perm[1][index(j, dj)] ^
+                        self.perm[2][index(k, dk)]
+                    ];
+                    let (fi, fj, fk) = (di as f32, dj as f32, dk as f32);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+
+                    acc += (fi * uu + (1.0 - fi) * (1.0 - uu)) *
+                           (fj * vv + (1.0 - fj) * (1.0 - vv)) *
+                           (fk * ww + (1.0 - fk) * (1.0 - ww)) * g.dot(weight);
+                }
+            }
+        }
+
+        acc
+    }
+
+    /// sum of `depth` noise octaves
+    fn turbulence(&self, p: Vec3, depth: u32) -> f32 {
+        let (mut acc, mut p, mut weight) = (0.0, p, 1.0);
+        for _ in 0..depth {
+            acc += weight * self.noise(p);
+            weight *= 0.5;
+            p *= 2.0;
+        }
+
+        acc.abs()
+    }
+}
+
+/// smooth perlin noise texture
+pub struct NoiseTexture {
+    noise: Perlin,
+    /// noise frequency
+    pub scale: f32,
+}
+
+impl NoiseTexture {
+    /// construct new noise texture, `seed` selects the noise pattern
+    pub fn new(scale: f32, seed: u64) -> Self {
+        NoiseTexture { noise: Perlin::new(seed), scale }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _: f32, _: f32, p: Vec3) -> Color {
+        Vec3::unit() * 0.5 * (1.0 + self.noise.noise(self.scale * p))
+    }
+}
+
+/// marble-like texture from perlin turbulence
+pub struct MarbleTexture {
+    noise: Perlin,
+    /// stripe frequency
+    pub scale: f32,
+}
+
+impl MarbleTexture {
+    /// construct new marble texture, `seed` selects the noise pattern
+    pub fn new(scale: f32, seed: u64) -> Self {
+        MarbleTexture { noise: Perlin::new(seed), scale }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _: f32, _: f32, p: Vec3) -> Color {
+        Vec3::unit() * 0.5 * (1.0 + (self.scale * p.z + 10.0 * self.noise.turbulence(p, 7)).sin())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checker() {
+        let white = Vec3::unit();
+        let black = Vec3::zero();
+        let checker = CheckerTexture::new(
+            Arc::new(ConstantTexture::new(black)),
+            Arc::new(ConstantTexture::new(white)), 1.0);
+
+        assert_eq!(checker.value(0.0, 0.0, Vec3::new(1.0, 1.0, 1.0)), white);
+        assert_eq!(checker.value(0.0, 0.0, Vec3::new(-1.0, 1.0, 1.0)), black);
+    }
+
+    #[test]
+    fn test_image() {
+        let red = Vec3::new(1.0, 0.0, 0.0);
+        let blue = Vec3::new(0.0, 0.0, 1.0);
+        let image = ImageTexture::new(2, 1, vec![red, blue]);
+
+        assert_eq!(image.value(0.25, 0.5, Vec3::zero()), red);
+        assert_eq!(image.value(0.75, 0.5, Vec3::zero()), blue);
+        assert_eq!(image.value(1.0, 1.0, Vec3::zero()), blue);
+
+        let path = std::env::temp_dir().join("rtw_test_texture_empty.ppm");
+        std::fs::write(&path, "P3\n0 0\n255\n").unwrap();
+        let err = ImageTexture::load(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_noise_range() {
+        let noise = NoiseTexture::new(4.0, 1);
+        let marble = MarbleTexture::new(4.0, 1);
+        let mut rng = Sampler::from_seed(0);
+
+        for _ in 0..1000 {
+            let p = 10.0 * Vec3::new(rng.gen(), rng.gen(), rng.gen());
+            for c in [noise.value(0.0, 0.0, p), marble.value(0.0, 0.0, p)].iter() {
+                assert!(c.x >= 0.0 && c.x <= 1.0, "{:?} out of range", c);
+            }
+        }
+
+        // noise is zero at lattice points
+        assert_eq!(noise.value(0.0, 0.0, Vec3::new(1.0, 2.0, 3.0)), Vec3::unit() * 0.5);
+    }
+}
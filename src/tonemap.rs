@@ -0,0 +1,178 @@
+use crate::{luminance, Color, Vec3};
+
+/// curve compressing linear radiance into display range [0, 1]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneOperator {
+    /// hard clamp at 1
+    Clamp,
+    /// luminance based Reinhard `L / (1 + L)`
+    Reinhard,
+    /// Hable's filmic curve (Uncharted 2) with white point 11.2
+    Filmic,
+    /// Narkowicz's fit of the ACES reference rendering transform
+    Aces,
+}
+
+/// transfer function encoding display linear values
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Transfer {
+    /// gamma 2 approximation (`sqrt`) of the book
+    Gamma2,
+    /// exact piecewise sRGB curve
+    Srgb,
+}
+
+/// conversion of linear radiance to 8-bit display colors
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ToneMapping {
+    /// exposure adjustment in stops, radiance is scaled by `2^exposure`
+    pub exposure: f32,
+    /// tone curve
+    pub operator: ToneOperator,
+    /// display encoding
+    pub transfer: Transfer,
+    /// apply 4x4 ordered dithering before quantization
+    pub dither: bool,
+}
+
+impl Default for ToneMapping {
+    /// same as `vec_to_rgb`: clamp with gamma 2 and no dithering
+    fn default() -> Self {
+        ToneMapping {
+            exposure: 0.0,
+            operator: ToneOperator::Clamp,
+            transfer: Transfer::Gamma2,
+            dither: false,
+        }
+    }
+}
+
+/// normalized 4x4 bayer matrix
+const BAYER: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+fn filmic(x: f32) -> f32 {
+    const A: f32 = 0.15;
+    const B: f32 = 0.50;
+    const C: f32 = 0.10;
+    const D: f32 = 0.20;
+    const E: f32 = 0.02;
+    const F: f32 = 0.30;
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+}
+
+fn aces(x: f32) -> f32 {
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// exact sRGB opto-electronic transfer function
+pub fn srgb_oetf(v: f32) -> f32 {
+    if v <= 0.003_130_8 {
+        12.92 * v
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl ToneMapping {
+    /// map linear radiance to display encoded color, components within [0, 1]
+    pub fn map(&self, c: Color) -> Color {
+        let c = c * self.exposure.exp2();
+        let per_channel = |f: fn(f32) -> f32| Vec3::new(f(c.x.max(0.0)), f(c.y.max(0.0)), f(c.z.max(0.0)));
+
+        let c = match self.operator {
+            ToneOperator::Clamp => c,
+            ToneOperator::Reinhard => c / (1.0 + luminance(c).max(0.0)),
+            ToneOperator::Filmic => per_channel(filmic) / filmic(11.2),
+            ToneOperator::Aces => per_channel(aces),
+        };
+
+        let c = match self.transfer {
+            Transfer::Gamma2 => Vec3::new(c.x.sqrt(), c.y.sqrt(), c.z.sqrt()),
+            Transfer::Srgb => Vec3::new(srgb_oetf(c.x), srgb_oetf(c.y), srgb_oetf(c.z)),
+        };
+
+        c.clamp(0.0, 1.0)
+    }
+
+    /// map linear radiance of pixel (`x`, `y`) to 8-bit RGB, the position selects the dither threshold
+    pub fn to_rgb8(&self, c: Color, x: u32, y: u32) -> [u8; 3] {
+        let c = self.map(c);
+        if self.dither {
+            let t = (BAYER[(y % 4) as usize][(x % 4) as usize] + 0.5) / 16.0;
+            let q = |v: f32| (v * 255.0 + t).min(255.0) as u8;
+            [q(c.x), q(c.y), q(c.z)]
+        } else {
+            let c = c * 255.99;
+            [c.x as u8, c.y as u8, c.z as u8]
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec_to_rgb;
+
+    #[test]
+    fn test_default_matches_vec_to_rgb() {
+        let tm = ToneMapping::default();
+        for &v in [-1.0, 0.0, 0.001, 0.2, 0.5, 0.99, 1.0, 3.0].iter() {
+            let c = Vec3::new(v, v * 0.5, 0.3);
+            assert_eq!(tm.to_rgb8(c, 1, 2), vec_to_rgb(c));
+        }
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut tm = ToneMapping { transfer: Transfer::Srgb, ..ToneMapping::default() };
+        assert!((tm.map(Vec3::new(0.0, 0.5, 1.0)) - Vec3::new(0.0, srgb_oetf(0.5), 1.0)).norm() < 1e-6);
+        assert!((srgb_oetf(0.5) - 0.735_357).abs() < 1e-5);
+        assert!((srgb_oetf(0.002) - 0.025_84).abs() < 1e-5);
+
+        // exposure in stops
+        tm.exposure = -1.0;
+        assert_eq!(tm.map(Vec3::new(1.0, 0.5, 0.2)).x, srgb_oetf(0.5));
+
+        // curves are monotonic and keep highlights below white
+        tm.exposure = 0.0;
+        for &op in [ToneOperator::Reinhard, ToneOperator::Filmic, ToneOperator::Aces].iter() {
+            tm.operator = op;
+            let mut prev = -1.0;
+            for i in 0..100 {
+                let v = tm.map(Vec3::new(1.0, 1.0, 1.0) * (i as f32 * 0.1)).x;
+                assert!(v >= prev && v <= 1.0, "{:?} at {}", op, i);
+                prev = v;
+            }
+            assert!(tm.map(Vec3::zero()).norm() < 1e-6);
+        }
+        tm.operator = ToneOperator::Filmic;
+        assert!((tm.map(Vec3::new(11.2, 11.2, 11.2)).x - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_dither() {
+        let tm = ToneMapping { transfer: Transfer::Srgb, dither: true, ..ToneMapping::default() };
+
+        // a value between two levels averages out to that value over a 4x4 block
+        let level = 100.3 / 255.0;
+        let c = Vec3::new(1.0, 1.0, 1.0) * srgb_linear(level);
+        let sum = (0..16).map(|i| f32::from(tm.to_rgb8(c, i % 4, i / 4)[0])).sum::<f32>();
+        assert!((sum / 16.0 - 100.3).abs() < 0.1);
+
+        assert_eq!(tm.to_rgb8(Vec3::new(1.0, 1.0, 1.0), 3, 3), [255, 255, 255]);
+        assert_eq!(tm.to_rgb8(Vec3::zero(), 3, 3), [0, 0, 0]);
+    }
+
+    fn srgb_linear(v: f32) -> f32 {
+        if v <= 0.040_45 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use crate::{Aabb, HitRecord, Hitable, Mat4, Material, Ray, Sampler, Solid, Span, Vec3};
+
+/// instance of a shared object placed in the world by an affine transform
+pub struct Transformed<H: ?Sized> {
+    object: Arc<H>,
+    to_world: Mat4,
+    to_object: Mat4,
+    /// transforms object space normals to world space, the inverse transpose
+    normal_to_world: Mat4,
+}
+
+impl<H: Hitable + ?Sized> Transformed<H> {
+    /// construct new instance of `object` transformed to world space by `transform`,
+    /// panics if `transform` is singular
+    pub fn new(object: Arc<H>, transform: Mat4) -> Self {
+        Self::try_new(object, transform).expect("singular transform")
+    }
+
+    /// construct new instance of `object` transformed to world space by `transform`,
+    /// `None` if `transform` is singular
+    pub fn try_new(object: Arc<H>, transform: Mat4) -> Option<Self> {
+        let to_object = transform.inverse()?;
+        Some(Transformed {
+            object,
+            to_world: transform,
+            to_object,
+            normal_to_world: to_object.transpose(),
+        })
+    }
+
+    /// object to world transform
+    pub fn transform(&self) -> Mat4 {
+        self.to_world
+    }
+
+    /// `ray` in object space, unnormalized so distances `t` are the same in both spaces
+    fn object_ray(&self, ray: &Ray) -> Ray {
+        Ray::with_time(
+            self.to_object.transform_point(ray.origin),
+            self.to_object.transform_vector(ray.direction),
+            ray.time,
+        )
+    }
+
+    /// object space hit `rec` of `ray` moved back to world space
+    fn world_record<'a>(&self, ray: &Ray, mut rec: HitRecord<'a>) -> HitRecord<'a> {
+        rec.point = ray.point_at(rec.t);
+        rec.normal = self.normal_to_world.transform_vector(rec.normal).normalize();
+        rec
+    }
+}
+
+impl<H: Hitable + ?Sized> Hitable for Transformed<H> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        self.object.hit(&self.object_ray(ray), t_min, t_max).map(|rec| self.world_record(ray, rec))
+    }
+
+    /// box around the transformed corners of the object's box
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox = self.object.bounding_box()?;
+        let corner = |i: usize| {
+            let pick = |a: usize| if i & (1 << a) == 0 { bbox.min[a] } else { bbox.max[a] };
+            let p = self.to_world.transform_point(Vec3::new(pick(0), pick(1), pick(2)));
+            Aabb::new(p, p)
+        };
+        Some((1..8).fold(corner(0), |b, i| Aabb::surrounding(&b, &corner(i))))
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f32 {
+        let local = self.to_object.transform_vector(direction);
+        let pdf = self.object.pdf_value(self.to_object.transform_point(origin), local);
+        if pdf <= 0.0 {
+            return 0.0;
+        }
+
+        // change of solid angle under the linear map A: |det A| / |A w|^3 for unit w
+        let stretch = local.norm() / direction.norm();
+        pdf * self.to_object.determinant().abs() / (stretch * stretch * stretch)
+    }
+
+    fn random_direction(&self, origin: Vec3, sampler: &mut Sampler) -> Vec3 {
+        let local = self.object.random_direction(self.to_object.transform_point(origin), sampler);
+        self.to_world.transform_vector(local)
+    }
+
+    /// the instance is a light if the object itself is, aggregates are not sampled through transforms
+    fn emitters<'s>(&'s self, lights: &mut Vec<&'s dyn Hitable>) {
+        let mut inner = Vec::new();
+        self.object.emitters(&mut inner);
+        let object = Arc::as_ptr(&self.object) as *const u8;
+        if let [light] = inner[..] {
+            if light as *const dyn Hitable as *const u8 == object {
+                lights.push(self);
+            }
+        }
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        self.object.materials(materials);
+    }
+}
+
+impl<H: Solid + ?Sized> Solid for Transformed<H> {
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        self.object.spans(&self.object_ray(ray))
+            .into_iter()
+            .map(|span| Span {
+                enter: self.world_record(ray, span.enter),
+                exit: self.world_record(ray, span.exit),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DiffuseLight, Lambertian, Sphere};
+
+    #[test]
+    fn test_transformed() {
+        let sphere: Arc<dyn Hitable> = Arc::new(Sphere::new(Vec3::zero(), 1.0,
+            Box::new(Lambertian::new(Vec3::unit()))));
+        // ellipsoid with radii (2, 1, 1) rotated to lie along z, centered at (0, 0, -5)
+        let transform = Mat4::translation(Vec3::new(0.0, 0.0, -5.0)) *
+                        Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), 90.0) *
+                        Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let a = Transformed::new(sphere.clone(), transform);
+        let b = Transformed::new(sphere, Mat4::translation(Vec3::new(3.0, 0.0, 0.0)));
+
+        let rec = a.hit(&Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -2.0)), 0.001, f32::MAX).unwrap();
+        assert!((rec.t - 1.5).abs() < 1e-5);
+        assert!((rec.point - Vec3::new(0.0, 0.0, -3.0)).norm() < 1e-5);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).norm() < 1e-5);
+
+        // normal stays perpendicular to the stretched surface
+        let rec = a.hit(&Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0)), 0.001, f32::MAX).unwrap();
+        let expected = Vec3::new(0.0, rec.point.y, (rec.point.z + 5.0) / 4.0).normalize();
+        assert!((rec.normal - expected).norm() < 1e-5);
+
+        let bbox = a.bounding_box().unwrap();
+        assert!((bbox.min - Vec3::new(-1.0, -1.0, -7.0)).norm() < 1e-5);
+        assert!((bbox.max - Vec3::new(1.0, 1.0, -3.0)).norm() < 1e-5);
+
+        assert!(b.hit(&Ray::new(Vec3::new(3.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)), 0.001, f32::MAX).is_some());
+        assert!(b.hit(&Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)), 0.001, f32::MAX).is_none());
+
+        let sphere = b.object.clone();
+        assert!(Transformed::try_new(sphere, Mat4::scaling(Vec3::new(1.0, 0.0, 1.0))).is_none());
+    }
+
+    #[test]
+    fn test_transformed_light() {
+        let light = Arc::new(Sphere::new(Vec3::zero(), 1.0, Box::new(DiffuseLight::new(Vec3::unit()))));
+        let scaled = Transformed::new(light, Mat4::translation(Vec3::new(0.0, 0.0, -4.0)) *
+                                             Mat4::scaling(Vec3::new(0.5, 0.5, 0.5)));
+        let reference = Sphere::new(Vec3::new(0.0, 0.0, -4.0), 0.5, Box::new(DiffuseLight::new(Vec3::unit())));
+
+        let mut lights = Vec::new();
+        scaled.emitters(&mut lights);
+        assert_eq!(lights.len(), 1);
+
+        let mut sampler = Sampler::from_seed(1);
+        for _ in 0..100 {
+            let d = scaled.random_direction(Vec3::zero(), &mut sampler);
+            let expected = reference.pdf_value(Vec3::zero(), d);
+            assert!(expected > 0.0);
+            assert!((scaled.pdf_value(Vec3::zero(), d) - expected).abs() < 1e-3 * expected);
+        }
+    }
+}
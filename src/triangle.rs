@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use crate::Ray;
+use crate::Vec3;
+use crate::{Aabb, BvhNode, HitRecord, Hitable, Material};
+
+/// padding to keep bounding boxes of axis-aligned triangles from being flat
+const BBOX_PADDING: f32 = 1e-4;
+
+/// Möller–Trumbore ray triangle intersection, returns `t` and barycentric `(b1, b2)`
+fn intersect(ray: &Ray, v: &[Vec3; 3], t_min: f32, t_max: f32) -> Option<(f32, f32, f32)> {
+    let e1 = v[1] - v[0];
+    let e2 = v[2] - v[0];
+    let p = ray.direction.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < 1e-8 {
+        return None;
+    }
+
+    let inv_det = det.recip();
+    let s = ray.origin - v[0];
+    let b1 = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let q = s.cross(e1);
+    let b2 = ray.direction.dot(q) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = e2.dot(q) * inv_det;
+    match t < t_max && t > t_min {
+        true => Some((t, b1, b2)),
+        false => None,
+    }
+}
+
+fn bounding_box(v: &[Vec3; 3]) -> Aabb {
+    let pad = Vec3::unit() * BBOX_PADDING;
+    let b = Aabb::surrounding(&Aabb::new(v[0], v[0]), &Aabb::new(v[1], v[1]));
+    let b = Aabb::surrounding(&b, &Aabb::new(v[2], v[2]));
+    Aabb::new(b.min - pad, b.max + pad)
+}
+
+/// triangle hitable object, front face is counter-clockwise
+pub struct Triangle {
+    vertices: [Vec3; 3],
+    normal: Vec3,
+    material: Box<dyn Material>,
+}
+
+impl Triangle {
+    /// construct new triangle with vertices `v0`, `v1` and `v2`
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3, material: Box<dyn Material>) -> Self {
+        Triangle {
+            vertices: [v0, v1, v2],
+            normal: (v1 - v0).cross(v2 - v0).normalize(),
+            material,
+        }
+    }
+}
+
+impl Hitable for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let (t, b1, b2) = intersect(ray, &self.vertices, t_min, t_max)?;
+
+        Some(HitRecord {
+            t,
+            point: ray.point_at(t),
+            normal: self.normal,
+            u: b1,
+            v: b2,
+            material: self.material.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(bounding_box(&self.vertices))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.material.as_ref());
+    }
+}
+
+/// shared vertex data of a triangle mesh
+struct MeshData {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<[usize; 3]>,
+    material: Arc<dyn Material>,
+}
+
+/// indexed triangle mesh with optional per-vertex normals and uvs, sharing one material
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    bvh: BvhNode<'static>,
+}
+
+impl TriangleMesh {
+    /// construct new triangle mesh from vertex `positions` and face `indices`
+    ///
+    /// `normals` and `uvs` are per-vertex and must have the same length as `positions`
+    /// when present, missing normals fall back to face normals.
+    /// panics if `indices` is empty or any attribute is out of range.
+    pub fn new(positions: Vec<Vec3>, normals: Option<Vec<Vec3>>, uvs: Option<Vec<[f32; 2]>>,
+               indices: Vec<[usize; 3]>, material: Arc<dyn Material>) -> Self {
+        let normals = normals.unwrap_or_default();
+        let uvs = uvs.unwrap_or_default();
+        assert!(normals.is_empty() || normals.len() == positions.len(), "normal count mismatch");
+        assert!(uvs.is_empty() || uvs.len() == positions.len(), "uv count mismatch");
+        assert!(indices.iter().flatten().all(|&i| i < positions.len()), "vertex index out of range");
+
+        let data = Arc::new(MeshData { positions, normals, uvs, indices, material });
+        let faces = (0..data.indices.len())
+            .map(|face| Box::new(MeshTriangle { mesh: data.clone(), face }) as Box<dyn Hitable>)
+            .collect();
+
+        TriangleMesh {
+            data,
+            bvh: BvhNode::from_objects(faces),
+        }
+    }
+
+    /// number of triangles
+    pub fn len(&self) -> usize {
+        self.data.indices.len()
+    }
+
+    /// whether mesh contains no triangle
+    pub fn is_empty(&self) -> bool {
+        self.data.indices.is_empty()
+    }
+
+    /// individual triangles sharing this mesh's data, for use in other acceleration structures
+    pub fn triangles(&self) -> impl Iterator<Item = MeshTriangle> + '_ {
+        (0..self.len()).map(move |face| MeshTriangle { mesh: self.data.clone(), face })
+    }
+}
+
+impl Hitable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        self.bvh.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.data.material.as_ref());
+    }
+}
+
+/// single triangle referencing a `TriangleMesh`
+pub struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl MeshTriangle {
+    fn vertices(&self) -> [Vec3; 3] {
+        let [i0, i1, i2] = self.mesh.indices[self.face];
+        let p = &self.mesh.positions;
+        [p[i0], p[i1], p[i2]]
+    }
+}
+
+impl Hitable for MeshTriangle {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let v = self.vertices();
+        let (t, b1, b2) = intersect(ray, &v, t_min, t_max)?;
+        let b0 = 1.0 - b1 - b2;
+        let [i0, i1, i2] = self.mesh.indices[self.face];
+
+        let normal = match self.mesh.normals.is_empty() {
+            true => (v[1] - v[0]).cross(v[2] - v[0]),
+            false => {
+                let n = &self.mesh.normals;
+                b0 * n[i0] + b1 * n[i1] + b2 * n[i2]
+            }
+        };
+
+        let (u, v) = match self.mesh.uvs.is_empty() {
+            true => (b1, b2),
+            false => {
+                let uv = &self.mesh.uvs;
+                (b0 * uv[i0][0] + b1 * uv[i1][0] + b2 * uv[i2][0],
+                 b0 * uv[i0][1] + b1 * uv[i1][1] + b2 * uv[i2][1])
+            }
+        };
+
+        Some(HitRecord {
+            t,
+            point: ray.point_at(t),
+            normal: normal.normalize(),
+            u,
+            v,
+            material: self.mesh.material.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(bounding_box(&self.vertices()))
+    }
+
+    fn materials<'s>(&'s self, materials: &mut Vec<&'s dyn Material>) {
+        materials.push(self.mesh.material.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Lambertian;
+
+    fn quad_mesh(normals: Option<Vec<Vec3>>) -> TriangleMesh {
+        let positions = vec![
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.0),
+        ];
+        let uvs = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+        TriangleMesh::new(positions, normals, Some(uvs), vec![[0, 1, 2], [0, 2, 3]],
+            Arc::new(Lambertian::new(Vec3::unit())))
+    }
+
+    #[test]
+    fn test_triangle_hit() {
+        let tri = Triangle::new(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Box::new(Lambertian::new(Vec3::unit())));
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = tri.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        let ray = Ray::new(Vec3::new(0.9, 0.9, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(tri.hit(&ray, 0.001, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_mesh_uv() {
+        let mesh = quad_mesh(None);
+        assert_eq!(mesh.len(), 2);
+
+        let ray = Ray::new(Vec3::new(-0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert_eq!(rec.t, 1.0);
+        assert!((rec.u - 0.25).abs() < 1e-6);
+        assert!((rec.v - 0.75).abs() < 1e-6);
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_mesh_vertex_normals() {
+        let n = Vec3::new(0.0, 1.0, 1.0).normalize();
+        let mesh = quad_mesh(Some(vec![n; 4]));
+
+        let ray = Ray::new(Vec3::new(0.2, -0.3, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, f32::MAX).unwrap();
+        assert!((rec.normal - n).norm() < 1e-6);
+
+        let list = mesh.triangles().collect::<Vec<_>>();
+        assert_eq!(list.len(), 2);
+    }
+}
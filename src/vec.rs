@@ -85,6 +85,155 @@ impl Vec3 {
             z: self.x * other.y - self.y * other.x,
         }
     }
+
+    /// two unit vectors forming an orthonormal basis with this unit vector
+    /// see: Duff et al., Building an Orthonormal Basis, Revisited
+    pub fn orthonormal_basis(&self) -> (Vec3, Vec3) {
+        let sign = 1.0f32.copysign(self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+        (
+            Vec3::new(1.0 + sign * self.x * self.x * a, sign * b, -sign * self.x),
+            Vec3::new(b, sign + self.y * self.y * a, -self.y),
+        )
+    }
+
+    /// transform `local` coordinates from the basis (`orthonormal_basis`, self) to world space
+    pub fn from_local(&self, local: Vec3) -> Vec3 {
+        let (s, t) = self.orthonormal_basis();
+        local.x * s + local.y * t + local.z * *self
+    }
+}
+
+/// 4x4 matrix in row-major order, used as affine transform of column vectors
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    /// identity transform
+    pub fn identity() -> Self {
+        Mat4::from_linear([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], Vec3::zero())
+    }
+
+    /// affine transform with 3x3 `linear` part (row-major) followed by `translation`
+    pub fn from_linear(linear: [[f32; 3]; 3], translation: Vec3) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in linear.iter().enumerate() {
+            m[i][..3].copy_from_slice(row);
+            m[i][3] = translation[i];
+        }
+        m[3][3] = 1.0;
+        Mat4 { m }
+    }
+
+    /// translation by `offset`
+    pub fn translation(offset: Vec3) -> Self {
+        let mut t = Mat4::identity();
+        t.m[0][3] = offset.x;
+        t.m[1][3] = offset.y;
+        t.m[2][3] = offset.z;
+        t
+    }
+
+    /// scaling by `factors` along each axis
+    pub fn scaling(factors: Vec3) -> Self {
+        let mut t = Mat4::identity();
+        for i in 0..3 {
+            t.m[i][i] = factors[i];
+        }
+        t
+    }
+
+    /// counterclockwise rotation by `degrees` around `axis` (right-hand rule)
+    pub fn rotation(axis: Vec3, degrees: f32) -> Self {
+        let a = axis.normalize();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let c = 1.0 - cos;
+        Mat4::from_linear([
+            [cos + a.x * a.x * c, a.x * a.y * c - a.z * sin, a.x * a.z * c + a.y * sin],
+            [a.y * a.x * c + a.z * sin, cos + a.y * a.y * c, a.y * a.z * c - a.x * sin],
+            [a.z * a.x * c - a.y * sin, a.z * a.y * c + a.x * sin, cos + a.z * a.z * c],
+        ], Vec3::zero())
+    }
+
+    /// transposed matrix
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = self.m[j][i];
+            }
+        }
+        Mat4 { m }
+    }
+
+    /// determinant of the linear 3x3 part, equal to the full determinant of affine transforms
+    pub fn determinant(&self) -> f32 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) -
+        m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0]) +
+        m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// inverse of affine transform, `None` if singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        // inverse of linear part by cofactors, then undo the translation
+        let m = &self.m;
+        let cofactor = |i: usize, j: usize| {
+            let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+            let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let mut linear = [[0.0; 3]; 3];
+        for (i, row) in linear.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = cofactor(j, i) / det;
+            }
+        }
+
+        let inverse = Mat4::from_linear(linear, Vec3::zero());
+        let translation = -inverse.transform_vector(Vec3::new(m[0][3], m[1][3], m[2][3]));
+        Some(Mat4::from_linear(linear, translation))
+    }
+
+    /// transform point, applying translation
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.transform_vector(p) + Vec3::new(self.m[0][3], self.m[1][3], self.m[2][3])
+    }
+
+    /// transform direction, ignoring translation
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let row = |r: &[f32; 4]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+        Vec3::new(row(&self.m[0]), row(&self.m[1]), row(&self.m[2]))
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::identity()
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    /// composition, `(a * b)` applies `b` first
+    fn mul(self, other: Self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            }
+        }
+        Mat4 { m }
+    }
 }
 
 /// reflect incident ray `v` with surface normal `n`
@@ -349,4 +498,29 @@ mod test {
         let v3 = Vec3::new(0.0, 1.0, 0.0);
         assert_eq!(v3, v1.cross(v2));
     }
+
+    #[test]
+    fn test_mat4() {
+        let close = |a: Vec3, b: Vec3| (a - b).norm() < 1e-5;
+
+        let r = Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), 90.0);
+        assert!(close(r.transform_vector(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 0.0, -1.0)));
+
+        let t = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * r * Mat4::scaling(Vec3::new(2.0, 3.0, 4.0));
+        let p = Vec3::new(1.0, 1.0, 1.0);
+        assert!(close(t.transform_point(p), Vec3::new(5.0, 5.0, 1.0)));
+        assert!(close(t.transform_vector(p), Vec3::new(4.0, 3.0, -2.0)));
+        assert!((t.determinant() - 24.0).abs() < 1e-4);
+
+        let inv = t.inverse().unwrap();
+        assert!(close(inv.transform_point(t.transform_point(p)), p));
+        let id = t * inv;
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((id.m[i][j] - Mat4::identity().m[i][j]).abs() < 1e-5);
+            }
+        }
+
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
 }